@@ -0,0 +1,194 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    meta::ParseNestedMeta, punctuated::Punctuated, spanned::Spanned, Attribute, Error, ExprPath,
+    LitInt, LitStr, Path, Result, Token, WherePredicate,
+};
+
+/// How an enum's variant tag is written ahead of the variant's fields.
+#[derive(Clone, Copy, Default)]
+pub enum TagWidth {
+    U8,
+    U16,
+    #[default]
+    U32,
+    Varint,
+}
+
+impl TagWidth {
+    /// The largest tag this width can represent.
+    pub fn max(self) -> u64 {
+        match self {
+            TagWidth::U8 => u8::MAX as u64,
+            TagWidth::U16 => u16::MAX as u64,
+            TagWidth::U32 => u32::MAX as u64,
+            TagWidth::Varint => u64::MAX,
+        }
+    }
+
+    /// The most bytes a tag no greater than `max_tag` takes.
+    pub fn encoded_len(self, max_tag: u64) -> usize {
+        match self {
+            TagWidth::U8 => 1,
+            TagWidth::U16 => 2,
+            TagWidth::U32 => 4,
+            TagWidth::Varint => ((64 - max_tag.leading_zeros() as usize).div_ceil(7)).max(1),
+        }
+    }
+
+    /// Tokens that write the literal `tag` to `io`.
+    pub fn encode(self, tag: u64) -> TokenStream2 {
+        match self {
+            TagWidth::U8 => {
+                let tag = tag as u8;
+                quote!(::codec::Encode::encode(&#tag, io)?;)
+            }
+            TagWidth::U16 => {
+                let tag = tag as u16;
+                quote!(::codec::Encode::encode(&#tag, io)?;)
+            }
+            TagWidth::U32 => {
+                let tag = tag as u32;
+                quote!(::codec::Encode::encode(&#tag, io)?;)
+            }
+            TagWidth::Varint => quote!(::codec::__private::encode_varint(#tag, io)?;),
+        }
+    }
+
+    /// An expression that reads a tag from `io` as a `u64`.
+    pub fn decode(self) -> TokenStream2 {
+        match self {
+            TagWidth::U8 => quote!(<u8 as ::codec::Decode>::decode(io)? as u64),
+            TagWidth::U16 => quote!(<u16 as ::codec::Decode>::decode(io)? as u64),
+            TagWidth::U32 => quote!(<u32 as ::codec::Decode>::decode(io)? as u64),
+            TagWidth::Varint => quote!(::codec::__private::decode_varint(io)?),
+        }
+    }
+}
+
+/// Parses the string value of a `bound = "..."` attribute into where predicates.
+fn parse_bound(meta: &ParseNestedMeta) -> Result<Vec<WherePredicate>> {
+    let value: LitStr = meta.value()?.parse()?;
+    let predicates = value.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+    Ok(predicates.into_iter().collect())
+}
+
+/// Attributes placed on the struct or enum itself.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    /// `#[codec(tag_width = "u8" | "u16" | "u32" | "varint")]`
+    pub tag_width: Option<(TagWidth, Span)>,
+    /// `#[codec(bound = "T: Trait")]`, replacing every inferred bound
+    pub bound: Option<Vec<WherePredicate>>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut parsed = ContainerAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("codec")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tag_width") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    let width = match value.value().as_str() {
+                        "u8" => TagWidth::U8,
+                        "u16" => TagWidth::U16,
+                        "u32" => TagWidth::U32,
+                        "varint" => TagWidth::Varint,
+                        _ => {
+                            return Err(Error::new_spanned(
+                                &value,
+                                "expected one of \"u8\", \"u16\", \"u32\" or \"varint\"",
+                            ))
+                        }
+                    };
+                    parsed.tag_width = Some((width, value.span()));
+                    Ok(())
+                } else if meta.path.is_ident("bound") {
+                    parsed.bound = Some(parse_bound(&meta)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown codec container attribute"))
+                }
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Attributes placed on an enum variant.
+#[derive(Default)]
+pub struct VariantAttrs {
+    /// `#[codec(tag = 7)]`
+    pub tag: Option<(u64, Span)>,
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut parsed = VariantAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("codec")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tag") {
+                    let value: LitInt = meta.value()?.parse()?;
+                    parsed.tag = Some((value.base10_parse()?, value.span()));
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown codec variant attribute"))
+                }
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Attributes placed on a struct or variant field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// `#[codec(skip)]`: the field is not encoded and is filled in on decode
+    pub skip: bool,
+    /// `#[codec(default = "path::to::fn")]`: how a skipped field is filled in, instead of
+    /// `Default::default`
+    pub default: Option<ExprPath>,
+    /// `#[codec(with = "path::to::module")]`: a module providing `encode` and `decode` functions
+    /// to use for this field instead of its `Codec` impl
+    pub with: Option<Path>,
+    /// `#[codec(bound = "T: Trait")]`, replacing the bounds inferred from this field's type
+    pub bound: Option<Vec<WherePredicate>>,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut parsed = FieldAttrs::default();
+        let mut span = None;
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("codec")) {
+            span = Some(attr.span());
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    parsed.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    parsed.default = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    parsed.with = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("bound") {
+                    parsed.bound = Some(parse_bound(&meta)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown codec field attribute"))
+                }
+            })?;
+        }
+        if let Some(span) = span {
+            if parsed.skip && parsed.with.is_some() {
+                return Err(Error::new(span, "`skip` and `with` cannot be combined"));
+            }
+            if parsed.default.is_some() && !parsed.skip {
+                return Err(Error::new(span, "`default` requires `skip`"));
+            }
+        }
+        Ok(parsed)
+    }
+}
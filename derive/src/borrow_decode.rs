@@ -0,0 +1,45 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_quote, DeriveInput, Error, GenericParam, Result, WherePredicate};
+
+use crate::bounded_generics;
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    if let Some(lifetime) = input
+        .generics
+        .lifetimes()
+        .find(|l| l.lifetime.ident == "de")
+    {
+        return Err(Error::new_spanned(
+            lifetime,
+            "the lifetime `'de` is reserved for the input `BorrowDecode` borrows from",
+        ));
+    }
+    let expanded = crate::expand(input, &quote!(::codec::BorrowDecode::borrow_decode))?;
+
+    let mut generics = bounded_generics(input, &expanded, quote!(::codec::BorrowDecode<'de>));
+    let outlives = input.generics.lifetimes().map(|param| -> WherePredicate {
+        let lifetime = &param.lifetime;
+        parse_quote!('de: #lifetime)
+    });
+    generics.make_where_clause().predicates.extend(outlives);
+    generics
+        .params
+        .insert(0, GenericParam::Lifetime(parse_quote!('de)));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let decode = &expanded.decode;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::codec::BorrowDecode<'de> for #name #ty_generics #where_clause {
+            #[inline]
+            fn borrow_decode(
+                io: &mut impl ::codec::io::BorrowRead<'de>,
+            ) -> ::core::result::Result<Self, ::codec::io::ReadError> {
+                #decode
+            }
+        }
+    })
+}
@@ -0,0 +1,63 @@
+use std::collections::BTreeSet;
+
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{parse_quote, visit::Visit, Generics, Ident, Type, TypePath, WherePredicate};
+
+/// Infers `bound` for every type parameter that appears in one of `field_types`, ignoring
+/// appearances inside `PhantomData` since it encodes to nothing.
+pub fn infer_bounds<'a>(
+    generics: &Generics,
+    field_types: impl IntoIterator<Item = &'a Type>,
+    bound: TokenStream2,
+) -> Vec<WherePredicate> {
+    let params: BTreeSet<&Ident> = generics.type_params().map(|p| &p.ident).collect();
+    let mut visitor = TypeParamVisitor {
+        params: &params,
+        used: BTreeSet::new(),
+    };
+    for ty in field_types {
+        visitor.visit_type(ty);
+    }
+    generics
+        .type_params()
+        .map(|param| &param.ident)
+        .filter(|ident| visitor.used.contains(*ident))
+        .map(|ident| parse_quote!(#ident: #bound))
+        .collect()
+}
+
+/// Appends `predicates` to the where clause of `generics`.
+pub fn with_predicates(
+    generics: &Generics,
+    predicates: impl IntoIterator<Item = WherePredicate>,
+) -> Generics {
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+struct TypeParamVisitor<'p> {
+    params: &'p BTreeSet<&'p Ident>,
+    used: BTreeSet<Ident>,
+}
+
+impl<'ast> Visit<'ast> for TypeParamVisitor<'_> {
+    fn visit_type_path(&mut self, ty: &'ast TypePath) {
+        if ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|last| last.ident == "PhantomData")
+        {
+            return;
+        }
+        if ty.qself.is_none() && ty.path.leading_colon.is_none() {
+            if let Some(first) = ty.path.segments.first() {
+                if self.params.contains(&first.ident) {
+                    self.used.insert(first.ident.clone());
+                }
+            }
+        }
+        syn::visit::visit_type_path(self, ty);
+    }
+}
@@ -0,0 +1,586 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, Data, DataEnum, DeriveInput, Error, Field,
+    Fields, GenericParam, Generics, Ident, Path, Result, Type, WherePredicate,
+};
+
+mod attr;
+mod borrow_decode;
+mod bounds;
+mod max_encoded_len;
+
+use attr::{ContainerAttrs, FieldAttrs, TagWidth, VariantAttrs};
+
+/// Derives `codec::Encode` and `codec::Decode` for a struct or enum.
+///
+/// Fields are encoded one after another in declaration order with no framing of their own, and
+/// every generic type parameter that appears in a field type (outside of `PhantomData`) receives
+/// a `codec::Encode` bound on the `Encode` impl and a `codec::Decode` bound on the `Decode` impl.
+///
+/// Enum values are prefixed with a variant tag. Tags count up from `0` in declaration order, an
+/// explicit tag can be given with `#[codec(tag = 7)]` on a variant (later variants continue from
+/// it), and the tag is written as a `u32` unless the enum is annotated with
+/// `#[codec(tag_width = "u8" | "u16" | "u32" | "varint")]`. Decoding an unknown tag fails with
+/// `ReadError::InvalidTag` naming the enum. Errors decoding a field record the field, variant and
+/// type names through `ReadError::in_field` and friends, so that they read like
+/// `in Order.items[3].price`.
+///
+/// `BorrowDecode` is implemented too, by decoding an owned value, so that the type can be nested
+/// in types deriving `BorrowDecode`.
+///
+/// The `Decode` impl also overrides `decode_in_place` to decode every field in place, so that a
+/// reused value keeps its buffers. An enum's fields are only reused if the value already holds
+/// the decoded variant.
+///
+/// Fields accept the following attributes:
+///
+/// - `#[codec(skip)]` leaves the field out of the encoding and fills it with
+///   `Default::default()` on decode, or with the function given by
+///   `#[codec(skip, default = "path::to::fn")]`.
+/// - `#[codec(with = "path::to::module")]` encodes the field with `module::encode(&T, io)` and
+///   decodes it with `module::decode(io)`, so the field type does not need to implement
+///   `Encode` or `Decode`. A type implementing `codec::with::CodecWith` for the field type works
+///   too.
+/// - `#[codec(bound = "T: Trait")]` replaces the bounds inferred from the field's type.
+///
+/// `#[codec(bound = "...")]` on the container replaces every inferred bound instead. Explicit
+/// bounds apply to both impls.
+#[proc_macro_derive(Codec, attributes(codec))]
+pub fn derive_codec(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let expanded = expand(&input, &owned_decode()).map(|expanded| {
+        let encode = impl_encode(&input, &expanded);
+        let decode = impl_decode(&input, &expanded);
+        quote!(#encode #decode)
+    });
+    into_output(expanded)
+}
+
+/// Derives `codec::Encode` alone, for types that are never decoded or that hold references.
+/// Accepts the same attributes as `Codec`.
+#[proc_macro_derive(Encode, attributes(codec))]
+pub fn derive_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_output(expand(&input, &owned_decode()).map(|expanded| impl_encode(&input, &expanded)))
+}
+
+/// Derives `codec::Decode` alone, for types that are never encoded, along with a `BorrowDecode`
+/// impl that decodes an owned value. Accepts the same attributes as `Codec`.
+#[proc_macro_derive(Decode, attributes(codec))]
+pub fn derive_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_output(expand(&input, &owned_decode()).map(|expanded| impl_decode(&input, &expanded)))
+}
+
+/// Derives `codec::BorrowDecode<'de>` for a struct or enum that borrows from the input it is
+/// decoded from, such as one with `&'a str` or `&'a [u8]` fields.
+///
+/// Types without borrowed fields should derive `Decode` instead, which provides `BorrowDecode`
+/// too, so the two derives cannot be combined. Fields may be `Option`s, `Vec`s, tuples or arrays
+/// of borrowed values. Fields are laid out exactly as `Codec` would lay them out and accept the same
+/// attributes. The impl is generic over the input lifetime `'de`, which outlives every lifetime
+/// parameter of the type and can be named in `bound` attributes, so the type may not declare its
+/// own `'de`.
+#[proc_macro_derive(BorrowDecode, attributes(codec))]
+pub fn derive_borrow_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_output(borrow_decode::expand(&input))
+}
+
+/// Derives `codec::MaxEncodedLen` for a struct or enum whose `Encode` impl was derived.
+///
+/// A struct's maximum is the sum of its fields' maximums, and an enum's is the widest its tag can
+/// be plus the largest variant. Skipped fields count for nothing, and fields encoded `with` a
+/// custom strategy are rejected since their length is unknown. Type parameters receive a
+/// `codec::MaxEncodedLen` bound, and the `bound` and `tag_width` attributes behave as they do
+/// for `Codec`.
+#[proc_macro_derive(MaxEncodedLen, attributes(codec))]
+pub fn derive_max_encoded_len(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_output(max_encoded_len::expand(input))
+}
+
+fn into_output(expanded: Result<TokenStream2>) -> TokenStream {
+    match expanded {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// The bodies of `encode`, of a decode function and of `decode_in_place` if the type overrides
+/// it, and what the impl bounds are inferred from.
+struct Expanded<'a> {
+    attrs: ContainerAttrs,
+    encode: TokenStream2,
+    decode: TokenStream2,
+    decode_in_place: Option<TokenStream2>,
+    bounded: Bounded<'a>,
+}
+
+/// Expands the bodies of `encode` and of a decode function that decodes fields with `decode`.
+fn expand<'a>(input: &'a DeriveInput, decode: &TokenStream2) -> Result<Expanded<'a>> {
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+    let mut bounded = Bounded::default();
+    let (encode, decode, decode_in_place) = match &input.data {
+        Data::Struct(data) => {
+            if let Some((_, span)) = attrs.tag_width {
+                return Err(Error::new(span, "`tag_width` is only valid on enums"));
+            }
+            let fields = parse_fields(&data.fields, &mut bounded)?;
+            let pattern = pattern(quote!(Self), &data.fields, &fields);
+            let encode_fields = encode_fields(&fields);
+            let context = field_context(&input.ident, None);
+            let construct = construct(quote!(Self), &data.fields, &fields, decode, &context);
+            let encode = quote! {
+                let #pattern = self;
+                #encode_fields
+                ::core::result::Result::Ok(())
+            };
+            let pattern = pattern_all(quote!(Self), &data.fields);
+            let in_place = decode_fields_in_place(&data.fields, &fields, &context);
+            let decode_in_place = nested_in_place(quote! {
+                let #pattern = self;
+                #in_place
+            });
+            (encode, nested(construct), Some(decode_in_place))
+        }
+        Data::Enum(data) => expand_enum(&input.ident, &attrs, data, &mut bounded, decode)?,
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "codec traits cannot be derived for unions",
+            ))
+        }
+    };
+    Ok(Expanded {
+        attrs,
+        encode,
+        decode,
+        decode_in_place,
+        bounded,
+    })
+}
+
+/// The generics of `input` with `bound` added to every type parameter used by an encoded field,
+/// or with the explicit `bound` attributes if the container has one.
+fn bounded_generics(input: &DeriveInput, expanded: &Expanded, bound: TokenStream2) -> Generics {
+    match &expanded.attrs.bound {
+        Some(predicates) => bounds::with_predicates(&input.generics, predicates.iter().cloned()),
+        None => bounds::with_predicates(
+            &input.generics,
+            bounds::infer_bounds(
+                &input.generics,
+                expanded.bounded.types.iter().copied(),
+                bound,
+            )
+            .into_iter()
+            .chain(expanded.bounded.predicates.iter().cloned()),
+        ),
+    }
+}
+
+/// The function decoding a field as an owned value.
+fn owned_decode() -> TokenStream2 {
+    quote!(::codec::Decode::decode)
+}
+
+fn impl_encode(input: &DeriveInput, expanded: &Expanded) -> TokenStream2 {
+    let name = &input.ident;
+    let generics = bounded_generics(input, expanded, quote!(::codec::Encode));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let encode = &expanded.encode;
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::codec::Encode for #name #ty_generics #where_clause {
+            #[inline]
+            fn encode(
+                &self,
+                io: &mut impl ::codec::io::Write,
+            ) -> ::core::result::Result<(), ::codec::io::WriteError> {
+                #encode
+            }
+        }
+    }
+}
+
+fn impl_decode(input: &DeriveInput, expanded: &Expanded) -> TokenStream2 {
+    let name = &input.ident;
+    let generics = bounded_generics(input, expanded, quote!(::codec::Decode));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let decode = &expanded.decode;
+    let decode_in_place = expanded.decode_in_place.as_ref().map(|decode_in_place| {
+        quote! {
+            #[inline]
+            fn decode_in_place(
+                &mut self,
+                io: &mut impl ::codec::io::Read,
+            ) -> ::core::result::Result<(), ::codec::io::ReadError> {
+                #decode_in_place
+            }
+        }
+    });
+    // Owned types decode from borrowed input like from any other. The lifetime is named so as not
+    // to clash with the type's own.
+    let mut borrow_generics = generics.clone();
+    borrow_generics
+        .params
+        .insert(0, GenericParam::Lifetime(parse_quote!('__de)));
+    let (borrow_impl_generics, _, _) = borrow_generics.split_for_impl();
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::codec::Decode for #name #ty_generics #where_clause {
+            #[inline]
+            fn decode(
+                io: &mut impl ::codec::io::Read,
+            ) -> ::core::result::Result<Self, ::codec::io::ReadError> {
+                #decode
+            }
+
+            #decode_in_place
+        }
+
+        #[automatically_derived]
+        impl #borrow_impl_generics ::codec::BorrowDecode<'__de> for #name #ty_generics
+            #where_clause
+        {
+            #[inline]
+            fn borrow_decode(
+                io: &mut impl ::codec::io::BorrowRead<'__de>,
+            ) -> ::core::result::Result<Self, ::codec::io::ReadError> {
+                <Self as ::codec::Decode>::decode(io)
+            }
+        }
+    }
+}
+
+/// The enum half of [`expand`], matching on the variant to encode and on the tag to decode.
+///
+/// Decoding in place reuses the current value's fields if it is already the decoded variant.
+/// Empty enums keep the default `decode_in_place`, since there is nothing to reuse.
+fn expand_enum<'a>(
+    name: &Ident,
+    attrs: &ContainerAttrs,
+    data: &'a DataEnum,
+    bounded: &mut Bounded<'a>,
+    decode: &TokenStream2,
+) -> Result<(TokenStream2, TokenStream2, Option<TokenStream2>)> {
+    let width = attrs.tag_width.map(|(width, _)| width).unwrap_or_default();
+    let tags = variant_tags(data, width)?;
+    let mut encode_arms = Vec::new();
+    let mut decode_arms = Vec::new();
+    let mut in_place_arms = Vec::new();
+    for (variant, tag) in data.variants.iter().zip(tags) {
+        let ident = &variant.ident;
+        let fields = parse_fields(&variant.fields, bounded)?;
+        let pattern = pattern(quote!(Self::#ident), &variant.fields, &fields);
+        let encode_tag = width.encode(tag);
+        let encode_fields = encode_fields(&fields);
+        encode_arms.push(quote! {
+            #pattern => {
+                #encode_tag
+                #encode_fields
+            }
+        });
+        let context = field_context(name, Some(ident));
+        let construct = construct(
+            quote!(Self::#ident),
+            &variant.fields,
+            &fields,
+            decode,
+            &context,
+        );
+        let pattern = pattern_all(quote!(Self::#ident), &variant.fields);
+        let in_place = decode_fields_in_place(&variant.fields, &fields, &context);
+        in_place_arms.push(quote! {
+            #tag => match self {
+                #pattern => {
+                    #in_place
+                }
+                #[allow(unreachable_patterns)]
+                __value => *__value = #construct,
+            },
+        });
+        decode_arms.push(quote! {
+            #tag => #construct,
+        });
+    }
+
+    let ty = name.to_string();
+    let decode_tag = width.decode();
+    // A reference to an uninhabited enum is still inhabited as far as exhaustiveness goes, so
+    // empty enums have to match on the dereferenced value instead.
+    let encode = if data.variants.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote! {
+            match self {
+                #(#encode_arms)*
+            }
+            ::core::result::Result::Ok(())
+        }
+    };
+    let invalid = quote! {
+        ::codec::io::ReadError::InvalidTag {
+            ty: #ty,
+            tag,
+        }
+    };
+    if data.variants.is_empty() {
+        let decode = quote! {
+            let tag = #decode_tag;
+            ::core::result::Result::Err(#invalid)
+        };
+        return Ok((encode, decode, None));
+    }
+    let decode = nested(quote! {
+        match #decode_tag {
+            #(#decode_arms)*
+            tag => return ::core::result::Result::Err(#invalid),
+        }
+    });
+    let decode_in_place = nested_in_place(quote! {
+        match #decode_tag {
+            #(#in_place_arms)*
+            tag => return ::core::result::Result::Err(#invalid),
+        }
+    });
+    Ok((encode, decode, Some(decode_in_place)))
+}
+
+/// Assigns each variant its tag, counting up from `0` or from the previous explicit tag, and
+/// checks that the tags are distinct and fit in `width`.
+fn variant_tags(data: &DataEnum, width: TagWidth) -> Result<Vec<u64>> {
+    let mut tags = Vec::new();
+    let mut next_tag = Some(0u64);
+    for variant in &data.variants {
+        let variant_attrs = VariantAttrs::parse(&variant.attrs)?;
+        let (tag, span) = match variant_attrs.tag {
+            Some(tag) => tag,
+            None => match next_tag {
+                Some(tag) => (tag, variant.ident.span()),
+                None => {
+                    return Err(Error::new_spanned(
+                        &variant.ident,
+                        "implicit tag overflows u64, add an explicit `#[codec(tag = ...)]`",
+                    ))
+                }
+            },
+        };
+        if tag > width.max() {
+            return Err(Error::new(
+                span,
+                "tag does not fit in the enum's `tag_width`",
+            ));
+        }
+        if tags.contains(&tag) {
+            return Err(Error::new(span, format!("duplicate tag {}", tag)));
+        }
+        tags.push(tag);
+        next_tag = tag.checked_add(1);
+    }
+    Ok(tags)
+}
+
+/// Wraps the expression decoding a value in a level of nesting, so that readers can bound the
+/// depth of recursive types.
+fn nested(value: TokenStream2) -> TokenStream2 {
+    quote! {
+        ::codec::io::Read::enter(io)?;
+        let __value = #value;
+        ::codec::io::Read::leave(io);
+        ::core::result::Result::Ok(__value)
+    }
+}
+
+/// Like [`nested`], for a `decode_in_place` body that assigns rather than returns the value.
+fn nested_in_place(body: TokenStream2) -> TokenStream2 {
+    quote! {
+        ::codec::io::Read::enter(io)?;
+        #body
+        ::codec::io::Read::leave(io);
+        ::core::result::Result::Ok(())
+    }
+}
+
+/// Field types to infer bounds from, plus any explicit field-level bounds.
+#[derive(Default)]
+struct Bounded<'a> {
+    types: Vec<&'a Type>,
+    predicates: Vec<WherePredicate>,
+}
+
+/// Parses the attributes of every field, recording what each contributes to the impl bounds.
+fn parse_fields<'a>(fields: &'a Fields, bounded: &mut Bounded<'a>) -> Result<Vec<FieldAttrs>> {
+    fields
+        .iter()
+        .map(|field| {
+            let attrs = FieldAttrs::parse(&field.attrs)?;
+            match &attrs.bound {
+                Some(predicates) => bounded.predicates.extend(predicates.iter().cloned()),
+                None if attrs.skip || attrs.with.is_some() => {}
+                None => bounded.types.push(&field.ty),
+            }
+            Ok(attrs)
+        })
+        .collect()
+}
+
+/// The local name each field is bound to when destructuring.
+fn binding(index: usize) -> Ident {
+    format_ident!("__field_{}", index)
+}
+
+/// A pattern that binds every encoded field of `path` to its [`binding`].
+fn pattern(path: TokenStream2, fields: &Fields, attrs: &[FieldAttrs]) -> TokenStream2 {
+    let bindings = attrs.iter().enumerate().map(|(i, attrs)| {
+        if attrs.skip {
+            quote!(_)
+        } else {
+            let binding = binding(i);
+            quote!(#binding)
+        }
+    });
+    fields_pattern(path, fields, bindings)
+}
+
+/// A pattern that binds every field of `path`, skipped or not, to its [`binding`].
+fn pattern_all(path: TokenStream2, fields: &Fields) -> TokenStream2 {
+    let bindings = (0..fields.len()).map(|i| {
+        let binding = binding(i);
+        quote!(#binding)
+    });
+    fields_pattern(path, fields, bindings)
+}
+
+fn fields_pattern(
+    path: TokenStream2,
+    fields: &Fields,
+    bindings: impl Iterator<Item = TokenStream2>,
+) -> TokenStream2 {
+    match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|f| &f.ident);
+            quote!(#path { #(#names: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => path,
+    }
+}
+
+/// Encodes every field bound by [`pattern`] in declaration order.
+fn encode_fields(attrs: &[FieldAttrs]) -> TokenStream2 {
+    let encodes = attrs.iter().enumerate().map(|(i, attrs)| {
+        let binding = binding(i);
+        match (&attrs.with, attrs.skip) {
+            (_, true) => quote!(),
+            (Some(with), _) => {
+                let encode = with_call(with, quote!(encode(#binding, io)));
+                quote!(#encode?;)
+            }
+            (None, _) => quote!(::codec::Encode::encode(#binding, io)?;),
+        }
+    });
+    quote!(#(#encodes)*)
+}
+
+/// The calls adding the path to a field of type `ty`, or of its `variant`, to a decode error.
+fn field_context(ty: &Ident, variant: Option<&Ident>) -> TokenStream2 {
+    let ty = ty.unraw().to_string();
+    let variant = variant.map(|variant| {
+        let variant = variant.unraw().to_string();
+        quote!(.in_variant(#variant))
+    });
+    quote!(#variant.in_type(#ty))
+}
+
+/// The name a field goes by in decode errors: its identifier, or its index in a tuple.
+fn field_name(index: usize, field: &Field) -> String {
+    match &field.ident {
+        Some(ident) => ident.unraw().to_string(),
+        None => index.to_string(),
+    }
+}
+
+/// Builds a value of `path` by decoding every encoded field in declaration order with `decode`,
+/// adding the field's name and then `context` to any error.
+fn construct(
+    path: TokenStream2,
+    fields: &Fields,
+    attrs: &[FieldAttrs],
+    decode: &TokenStream2,
+    context: &TokenStream2,
+) -> TokenStream2 {
+    let values = fields
+        .iter()
+        .zip(attrs)
+        .enumerate()
+        .map(|(i, (field, attrs))| {
+            let name = field_name(i, field);
+            let value = match (&attrs.with, attrs.skip) {
+                (_, true) => return skipped_value(attrs),
+                (Some(with), _) => with_call(with, quote!(decode(io))),
+                (None, _) => quote!(#decode(io)),
+            };
+            quote!(#value.map_err(|e| ::codec::io::ReadError::in_field(e, #name)#context)?)
+        });
+    match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|f| &f.ident);
+            quote!(#path { #(#names: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+        Fields::Unit => path,
+    }
+}
+
+/// Decodes over every field bound by [`pattern_all`], resetting skipped fields as `decode` would
+/// and adding the field's name and then `context` to any error.
+fn decode_fields_in_place(
+    fields: &Fields,
+    attrs: &[FieldAttrs],
+    context: &TokenStream2,
+) -> TokenStream2 {
+    let decodes = fields
+        .iter()
+        .zip(attrs)
+        .enumerate()
+        .map(|(i, (field, attrs))| {
+            let binding = binding(i);
+            let name = field_name(i, field);
+            let context = quote!(|e| ::codec::io::ReadError::in_field(e, #name)#context);
+            match (&attrs.with, attrs.skip) {
+                (_, true) => {
+                    let value = skipped_value(attrs);
+                    quote!(*#binding = #value;)
+                }
+                (Some(with), _) => {
+                    let decode = with_call(with, quote!(decode(io)));
+                    quote!(*#binding = #decode.map_err(#context)?;)
+                }
+                (None, _) => {
+                    quote!(::codec::Decode::decode_in_place(#binding, io).map_err(#context)?;)
+                }
+            }
+        });
+    quote!(#(#decodes)*)
+}
+
+/// Calls a function of a `with` strategy. A strategy type's functions come from its `CodecWith`
+/// impl, so the trait is brought into scope for them; a module's functions resolve without it.
+fn with_call(with: &Path, call: TokenStream2) -> TokenStream2 {
+    quote!({
+        #[allow(unused_imports)]
+        use ::codec::with::CodecWith as _;
+        #with::#call
+    })
+}
+
+/// The value a skipped field is filled with on decode.
+fn skipped_value(attrs: &FieldAttrs) -> TokenStream2 {
+    match &attrs.default {
+        Some(default) => quote!(#default()),
+        None => quote!(::core::default::Default::default()),
+    }
+}
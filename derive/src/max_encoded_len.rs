@@ -0,0 +1,90 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Result};
+
+use crate::{attr::ContainerAttrs, bounds, parse_fields, variant_tags, Bounded};
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+    let mut bounded = Bounded::default();
+    let len = match &input.data {
+        Data::Struct(data) => {
+            if let Some((_, span)) = attrs.tag_width {
+                return Err(Error::new(span, "`tag_width` is only valid on enums"));
+            }
+            fields_len(&data.fields, &mut bounded)?
+        }
+        Data::Enum(data) if data.variants.is_empty() => quote!(0),
+        Data::Enum(data) => {
+            let width = attrs.tag_width.map(|(width, _)| width).unwrap_or_default();
+            let tags = variant_tags(data, width)?;
+            let tag_len = width.encoded_len(tags.iter().copied().max().unwrap_or_default());
+            let variant_lens = data
+                .variants
+                .iter()
+                .map(|variant| fields_len(&variant.fields, &mut bounded))
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                #tag_len + {
+                    let mut max = 0;
+                    #(
+                        let len = #variant_lens;
+                        if len > max {
+                            max = len;
+                        }
+                    )*
+                    max
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input,
+                "`MaxEncodedLen` cannot be derived for unions",
+            ))
+        }
+    };
+
+    let generics = match attrs.bound {
+        Some(predicates) => bounds::with_predicates(&input.generics, predicates),
+        None => bounds::with_predicates(
+            &input.generics,
+            bounds::infer_bounds(
+                &input.generics,
+                bounded.types,
+                quote!(::codec::MaxEncodedLen),
+            )
+            .into_iter()
+            .chain(bounded.predicates),
+        ),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::codec::MaxEncodedLen for #name #ty_generics #where_clause {
+            const MAX_ENCODED_LEN: usize = #len;
+        }
+    })
+}
+
+/// The sum of the maximum encoded lengths of every encoded field.
+fn fields_len<'a>(fields: &'a Fields, bounded: &mut Bounded<'a>) -> Result<TokenStream2> {
+    let attrs = parse_fields(fields, bounded)?;
+    let mut lens = Vec::new();
+    for (field, attrs) in fields.iter().zip(attrs) {
+        if attrs.skip {
+            continue;
+        }
+        if let Some(with) = attrs.with {
+            return Err(Error::new_spanned(
+                with,
+                "`MaxEncodedLen` cannot be derived for fields encoded `with` a custom strategy",
+            ));
+        }
+        let ty = &field.ty;
+        lens.push(quote!(<#ty as ::codec::MaxEncodedLen>::MAX_ENCODED_LEN));
+    }
+    Ok(quote!(0 #(+ #lens)*))
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use std::{fmt::Debug, net::Ipv4Addr, time::Duration};
+
+use codec::{io::ReadError, with::As, Codec, Decode};
+use common::{encode, roundtrip};
+
+/// Encodes a foreign `Ipv4Addr` as its four octets.
+mod ipv4 {
+    use std::net::Ipv4Addr;
+
+    use codec::io::{Read, ReadError, Write, WriteError};
+
+    pub fn encode(addr: &Ipv4Addr, io: &mut impl Write) -> Result<(), WriteError> {
+        io.write(&addr.octets())
+    }
+
+    pub fn decode(io: &mut impl Read) -> Result<Ipv4Addr, ReadError> {
+        io.read::<4>().map(Ipv4Addr::from)
+    }
+}
+
+/// Kept out of the way so that the derive sites below do not have `CodecWith` in scope.
+mod strategies {
+    use std::time::Duration;
+
+    use codec::{
+        io::{Read, ReadError, Write, WriteError},
+        with::CodecWith,
+        Decode, Encode,
+    };
+
+    /// Encodes a foreign `Duration` as whole milliseconds.
+    pub struct Millis;
+
+    impl CodecWith<Duration> for Millis {
+        fn encode(value: &Duration, io: &mut impl Write) -> Result<(), WriteError> {
+            (value.as_millis() as u64).encode(io)
+        }
+
+        fn decode(io: &mut impl Read) -> Result<Duration, ReadError> {
+            u64::decode(io).map(Duration::from_millis)
+        }
+    }
+}
+
+use strategies::Millis;
+
+#[derive(Codec, Debug, PartialEq)]
+struct Timeouts {
+    #[codec(with = "strategies::Millis")]
+    connect: Duration,
+    retries: Vec<As<Duration, Millis>>,
+}
+
+fn seven() -> u32 {
+    7
+}
+
+#[derive(Codec, Debug, PartialEq)]
+struct Skipped {
+    id: u8,
+    #[codec(skip)]
+    cache: Vec<u64>,
+    #[codec(skip, default = "seven")]
+    version: u32,
+}
+
+#[derive(Codec, Debug, PartialEq)]
+struct Peer {
+    #[codec(with = "ipv4")]
+    addr: Ipv4Addr,
+    port: u16,
+}
+
+#[derive(Codec, Debug, PartialEq)]
+enum Endpoint {
+    Local,
+    Remote(
+        #[codec(with = "ipv4")] Ipv4Addr,
+        #[codec(skip)] Option<String>,
+    ),
+}
+
+struct NotCodec;
+
+#[derive(Codec, Debug, PartialEq)]
+struct SkippedParam<T> {
+    value: u8,
+    #[codec(skip)]
+    ignored: Option<T>,
+}
+
+trait Wire {
+    type Repr: Codec;
+}
+
+struct U16Wire;
+
+impl Wire for U16Wire {
+    type Repr = u16;
+}
+
+#[derive(Codec)]
+struct FieldBound<W: Wire> {
+    #[codec(bound = "W::Repr: Codec")]
+    repr: W::Repr,
+}
+
+#[derive(Codec)]
+#[codec(bound = "")]
+struct ContainerBound<W: Wire<Repr = u16>> {
+    repr: W::Repr,
+}
+
+#[test]
+fn test_skip() {
+    let value = Skipped {
+        id: 1,
+        cache: vec![1, 2, 3],
+        version: 1,
+    };
+    let bytes = encode(&value);
+    assert_eq!(bytes, [1]);
+    let decoded = Skipped::decode(&mut &bytes[..]).unwrap();
+    assert_eq!(
+        decoded,
+        Skipped {
+            id: 1,
+            cache: Vec::new(),
+            version: 7,
+        }
+    );
+}
+
+#[test]
+fn test_with() {
+    let peer = Peer {
+        addr: Ipv4Addr::new(10, 0, 0, 1),
+        port: 80,
+    };
+    assert_eq!(roundtrip(peer), [10, 0, 0, 1, 80, 0]);
+    let remote = Endpoint::Remote(Ipv4Addr::LOCALHOST, None);
+    assert_eq!(roundtrip(remote), [1, 0, 0, 0, 127, 0, 0, 1]);
+    roundtrip(Endpoint::Local);
+}
+
+#[test]
+fn test_with_strategy() {
+    let timeouts = Timeouts {
+        connect: Duration::from_millis(250),
+        retries: vec![As::new(Duration::from_secs(1))],
+    };
+    let bytes = roundtrip(timeouts);
+    assert_eq!(bytes[..8], 250u64.to_le_bytes());
+}
+
+#[test]
+fn test_with_error() {
+    assert!(matches!(
+        Peer::decode(&mut &[10u8, 0][..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_skip_and_with_in_place() {
+    let mut value = Skipped {
+        id: 9,
+        cache: vec![4],
+        version: 1,
+    };
+    value.decode_in_place(&mut &[2u8][..]).unwrap();
+    assert_eq!(
+        value,
+        Skipped {
+            id: 2,
+            cache: vec![],
+            version: 7,
+        }
+    );
+    let mut endpoint = Endpoint::Remote(Ipv4Addr::LOCALHOST, Some(String::from("cached")));
+    let bytes = encode(&Endpoint::Remote(Ipv4Addr::BROADCAST, None));
+    endpoint.decode_in_place(&mut &bytes[..]).unwrap();
+    assert_eq!(endpoint, Endpoint::Remote(Ipv4Addr::BROADCAST, None));
+}
+
+#[test]
+fn test_skipped_param_is_unbounded() {
+    let value = SkippedParam::<NotCodec> {
+        value: 4,
+        ignored: None,
+    };
+    assert_eq!(encode(&value), [4]);
+}
+
+#[test]
+fn test_bound_overrides() {
+    let bytes = encode(&FieldBound::<U16Wire> { repr: 0x0102 });
+    assert_eq!(bytes, [2, 1]);
+    let decoded = FieldBound::<U16Wire>::decode(&mut &bytes[..]).unwrap();
+    assert_eq!(decoded.repr, 0x0102);
+    let decoded = ContainerBound::<U16Wire>::decode(&mut &bytes[..]).unwrap();
+    assert_eq!(encode(&decoded), bytes);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use codec::{bytes::ByteSlice, decode_borrowed, io::ReadError, BorrowDecode, Codec, Encode};
+use common::encode;
+
+#[derive(Codec, Debug, PartialEq)]
+struct Owned {
+    id: u32,
+    name: String,
+    payload: Vec<u8>,
+}
+
+#[derive(Encode, BorrowDecode, Debug, PartialEq)]
+struct Borrowed<'a> {
+    id: u32,
+    name: &'a str,
+    payload: &'a ByteSlice,
+}
+
+#[derive(Encode, BorrowDecode, Debug, PartialEq)]
+struct Nested<'a, 'b, T> {
+    inner: Borrowed<'a>,
+    tail: &'b [u8],
+    value: T,
+}
+
+#[derive(Codec, Debug, PartialEq)]
+struct OwnedBatch {
+    label: Option<String>,
+    tags: Vec<String>,
+    entries: Vec<Owned>,
+    header: Owned,
+}
+
+#[derive(Encode, BorrowDecode, Debug, PartialEq)]
+struct Batch<'a> {
+    label: Option<&'a str>,
+    tags: Vec<&'a str>,
+    entries: Vec<Borrowed<'a>>,
+    header: Owned,
+}
+
+#[derive(Codec, Debug, PartialEq)]
+#[codec(tag_width = "u8")]
+enum OwnedEvent {
+    Empty,
+    Text(String),
+    Pair { key: String, value: u16 },
+}
+
+#[derive(Encode, BorrowDecode, Debug, PartialEq)]
+#[codec(tag_width = "u8")]
+enum Event<'a> {
+    Empty,
+    Text(&'a str),
+    Pair { key: &'a str, value: u16 },
+}
+
+#[test]
+fn test_borrowed_struct() {
+    let owned = Owned {
+        id: 9,
+        name: String::from("blob"),
+        payload: vec![1, 2, 3, 4],
+    };
+    let bytes = encode(&owned);
+    let borrowed: Borrowed = decode_borrowed(&mut &bytes[..]).unwrap();
+    assert_eq!(borrowed.id, 9);
+    assert_eq!(borrowed.name, "blob");
+    assert_eq!(*borrowed.payload, [1, 2, 3, 4]);
+    let payload_start = bytes.len() - 4;
+    assert_eq!(borrowed.payload.as_ptr(), bytes[payload_start..].as_ptr());
+}
+
+#[test]
+fn test_borrowed_nested() {
+    let mut bytes = encode(&Owned {
+        id: 1,
+        name: String::from("a"),
+        payload: vec![],
+    });
+    bytes.extend(encode(&vec![5u8, 6]));
+    bytes.extend(encode(&-1i64));
+    let nested: Nested<i64> = decode_borrowed(&mut &bytes[..]).unwrap();
+    assert_eq!(nested.inner.name, "a");
+    assert_eq!(nested.tail, [5, 6]);
+    assert_eq!(nested.value, -1);
+}
+
+#[test]
+fn test_borrowed_enum() {
+    let bytes = encode(&OwnedEvent::Pair {
+        key: String::from("k"),
+        value: 3,
+    });
+    assert_eq!(
+        decode_borrowed::<Event>(&mut &bytes[..]).unwrap(),
+        Event::Pair { key: "k", value: 3 }
+    );
+    let bytes = encode(&OwnedEvent::Text(String::from("hi")));
+    assert_eq!(
+        decode_borrowed::<Event>(&mut &bytes[..]).unwrap(),
+        Event::Text("hi")
+    );
+    assert_eq!(
+        decode_borrowed::<Event>(&mut &[0u8][..]).unwrap(),
+        Event::Empty
+    );
+    assert!(matches!(
+        decode_borrowed::<Event>(&mut &[3u8][..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InvalidTag {
+            ty: "Event",
+            tag: 3
+        })
+    ));
+}
+
+#[test]
+fn test_borrowed_containers() {
+    let owned = Owned {
+        id: 3,
+        name: String::from("entry"),
+        payload: vec![9],
+    };
+    let bytes = encode(&OwnedBatch {
+        label: Some(String::from("batch")),
+        tags: vec![String::from("x"), String::from("yz")],
+        entries: vec![Owned { id: 4, ..owned }],
+        header: Owned {
+            id: 5,
+            name: String::new(),
+            payload: vec![],
+        },
+    });
+    let batch: Batch = decode_borrowed(&mut &bytes[..]).unwrap();
+    assert_eq!(batch.label, Some("batch"));
+    assert_eq!(batch.tags, ["x", "yz"]);
+    assert_eq!(batch.entries[0].name, "entry");
+    assert_eq!(*batch.entries[0].payload, [9]);
+    assert_eq!(batch.header.id, 5);
+    assert_eq!(batch.label.unwrap().as_ptr(), bytes[9..].as_ptr());
+}
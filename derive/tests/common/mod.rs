@@ -0,0 +1,20 @@
+//! Helpers shared by the derive tests. Not every test file uses all of them.
+#![allow(dead_code)]
+
+use std::fmt::Debug;
+
+use codec::{encode_to_vec, Codec, Encode};
+
+pub fn encode<T: Encode + ?Sized>(value: &T) -> Vec<u8> {
+    encode_to_vec(value).unwrap().into()
+}
+
+/// Encodes `value`, checks that it decodes back to itself using every byte, and returns the
+/// bytes.
+pub fn roundtrip<T: Codec + PartialEq + Debug>(value: T) -> Vec<u8> {
+    let bytes = encode(&value);
+    let mut slice = &bytes[..];
+    assert_eq!(T::decode(&mut slice).unwrap(), value);
+    assert!(slice.is_empty());
+    bytes
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use std::fmt::Debug;
+
+use codec::{io::ReadError, Codec, Decode};
+use common::{encode, roundtrip};
+
+#[derive(Codec, Debug, PartialEq)]
+enum Shape {
+    Empty,
+    Circle(u16),
+    Rect { w: u8, h: u8 },
+}
+
+#[derive(Codec, Debug, PartialEq)]
+#[codec(tag_width = "u8")]
+enum Explicit {
+    #[codec(tag = 7)]
+    Seven,
+    Eight(bool),
+    #[codec(tag = 2)]
+    Two,
+}
+
+#[derive(Codec, Debug, PartialEq)]
+#[codec(tag_width = "u16")]
+enum Wide {
+    #[codec(tag = 0x1234)]
+    A,
+}
+
+#[derive(Codec, Debug, PartialEq)]
+#[codec(tag_width = "varint")]
+enum Varint {
+    Small,
+    #[codec(tag = 300)]
+    Large(String),
+}
+
+#[derive(Codec, Debug, PartialEq)]
+enum Generic<T> {
+    Nothing,
+    Something(T),
+}
+
+#[derive(Codec, Debug, PartialEq)]
+enum Never {}
+
+#[test]
+fn test_default_u32_tags() {
+    assert_eq!(roundtrip(Shape::Empty), [0, 0, 0, 0]);
+    assert_eq!(roundtrip(Shape::Circle(5)), [1, 0, 0, 0, 5, 0]);
+    assert_eq!(roundtrip(Shape::Rect { w: 2, h: 3 }), [2, 0, 0, 0, 2, 3]);
+}
+
+#[test]
+fn test_explicit_tags() {
+    assert_eq!(roundtrip(Explicit::Seven), [7]);
+    assert_eq!(roundtrip(Explicit::Eight(true)), [8, 1]);
+    assert_eq!(roundtrip(Explicit::Two), [2]);
+}
+
+#[test]
+fn test_u16_tags() {
+    assert_eq!(roundtrip(Wide::A), [0x34, 0x12]);
+}
+
+#[test]
+fn test_varint_tags() {
+    assert_eq!(roundtrip(Varint::Small), [0]);
+    let bytes = roundtrip(Varint::Large(String::from("x")));
+    assert_eq!(&bytes[..2], [0xAC, 0x02]);
+}
+
+#[test]
+fn test_generic_enum() {
+    roundtrip(Generic::<Vec<u8>>::Nothing);
+    roundtrip(Generic::Something(vec![1u8, 2]));
+}
+
+#[test]
+fn test_unknown_tag() {
+    assert!(matches!(
+        Shape::decode(&mut &[3u8, 0, 0, 0][..]),
+        Err(ReadError::InvalidTag {
+            ty: "Shape",
+            tag: 3
+        })
+    ));
+    assert!(matches!(
+        Explicit::decode(&mut &[0u8][..]),
+        Err(ReadError::InvalidTag {
+            ty: "Explicit",
+            tag: 0
+        })
+    ));
+    assert!(matches!(
+        Never::decode(&mut &[0u8, 0, 0, 0][..]),
+        Err(ReadError::InvalidTag {
+            ty: "Never",
+            tag: 0
+        })
+    ));
+}
+
+#[derive(Codec, Debug, PartialEq)]
+#[codec(tag_width = "u8")]
+enum Tree {
+    Leaf(u8),
+    Node(Box<Tree>, Box<Tree>),
+}
+
+#[test]
+fn test_recursive() {
+    let tree = Tree::Node(
+        Box::new(Tree::Leaf(1)),
+        Box::new(Tree::Node(Box::new(Tree::Leaf(2)), Box::new(Tree::Leaf(3)))),
+    );
+    let bytes = roundtrip(tree);
+    assert_eq!(bytes, [1, 0, 1, 1, 0, 2, 0, 3]);
+    assert!(codec::decode::<Tree>(&mut &bytes[..]).is_ok());
+}
+
+#[derive(Codec, Debug, PartialEq)]
+enum Message {
+    Ping,
+    Text { body: String },
+}
+
+#[test]
+fn test_decode_in_place() {
+    let mut message = Message::Text {
+        body: String::with_capacity(32),
+    };
+    let Message::Text { body } = &message else {
+        unreachable!()
+    };
+    let ptr = body.as_ptr();
+    let text = Message::Text {
+        body: String::from("in place"),
+    };
+    message.decode_in_place(&mut &encode(&text)[..]).unwrap();
+    assert_eq!(message, text);
+    let Message::Text { body } = &message else {
+        unreachable!()
+    };
+    assert_eq!(body.as_ptr(), ptr);
+
+    message
+        .decode_in_place(&mut &encode(&Message::Ping)[..])
+        .unwrap();
+    assert_eq!(message, Message::Ping);
+    message.decode_in_place(&mut &encode(&text)[..]).unwrap();
+    assert_eq!(message, text);
+    assert_eq!(
+        message.decode_in_place(&mut &[5u8, 0, 0, 0][..]),
+        Err(ReadError::InvalidTag {
+            ty: "Message",
+            tag: 5
+        })
+    );
+}
+
+#[test]
+fn test_decode_error_path() {
+    let bytes = encode(&Shape::Rect { w: 1, h: 2 });
+    let error = codec::decode::<Shape>(&mut &bytes[..5]).unwrap_err();
+    assert_eq!(
+        format!("{:?}", error),
+        "at offset 5 in Shape::Rect.h: there is not enough data to complete the requested read"
+    );
+    let error = codec::decode::<Shape>(&mut &[9u8, 0, 0, 0][..]).unwrap_err();
+    assert_eq!(
+        format!("{:?}", error),
+        "at offset 4: invalid tag 9 for Shape"
+    );
+}
+
+#[test]
+fn test_recursion_depth_limit() {
+    let bytes = vec![1u8; 100_000];
+    assert!(matches!(
+        codec::decode::<Tree>(&mut &bytes[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::DepthLimitExceeded)
+    ));
+    let limits = codec::io::Limits::DEFAULT.with_max_depth(3);
+    let shallow = [1u8, 0, 1, 0, 2];
+    assert!(codec::decode_with_limits::<Tree>(&mut &shallow[..], limits).is_ok());
+    let deeper = [1u8, 1, 0, 1, 0, 2, 0, 3];
+    assert!(matches!(
+        codec::decode_with_limits::<Tree>(&mut &deeper[..], limits)
+            .map_err(ReadError::into_innermost),
+        Err(ReadError::DepthLimitExceeded)
+    ));
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::marker::PhantomData;
+
+use codec::{encode_to_byte_array, varint::VarInt, Codec, Decode, MaxEncodedLen};
+use common::encode;
+
+#[derive(Codec, MaxEncodedLen, Debug, PartialEq)]
+struct Header {
+    version: u8,
+    length: u32,
+    flags: [bool; 3],
+    checksum: Option<u64>,
+}
+
+#[derive(Codec, MaxEncodedLen, Debug, PartialEq)]
+struct Unit;
+
+#[derive(Codec, MaxEncodedLen, Debug, PartialEq)]
+struct Skipping {
+    id: u16,
+    #[codec(skip)]
+    cache: String,
+}
+
+#[derive(Codec, MaxEncodedLen, Debug, PartialEq)]
+struct Generic<T, U> {
+    value: T,
+    _marker: PhantomData<U>,
+}
+
+#[derive(Codec, MaxEncodedLen, Debug, PartialEq)]
+enum Message {
+    Ping,
+    Data(Header),
+    Ack { id: u64 },
+}
+
+#[derive(Codec, MaxEncodedLen, Debug, PartialEq)]
+#[codec(tag_width = "varint")]
+enum Wide {
+    A(u8),
+    #[codec(tag = 300)]
+    B(VarInt<u32>),
+}
+
+#[derive(Codec, MaxEncodedLen, Debug, PartialEq)]
+enum Never {}
+
+#[test]
+fn test_struct() {
+    assert_eq!(Header::MAX_ENCODED_LEN, 1 + 4 + 3 + 9);
+    assert_eq!(Unit::MAX_ENCODED_LEN, 0);
+    assert_eq!(Skipping::MAX_ENCODED_LEN, 2);
+    assert_eq!(Generic::<u32, String>::MAX_ENCODED_LEN, 4);
+    let header = Header {
+        version: 1,
+        length: 2,
+        flags: [true; 3],
+        checksum: Some(3),
+    };
+    assert_eq!(encode(&header).len(), Header::MAX_ENCODED_LEN);
+}
+
+#[test]
+fn test_enum() {
+    assert_eq!(Message::MAX_ENCODED_LEN, 4 + Header::MAX_ENCODED_LEN);
+    assert_eq!(Wide::MAX_ENCODED_LEN, 2 + 5);
+    assert_eq!(Never::MAX_ENCODED_LEN, 0);
+    assert_eq!(
+        encode(&Wide::B(VarInt(u32::MAX))).len(),
+        Wide::MAX_ENCODED_LEN
+    );
+}
+
+#[test]
+fn test_encode_to_byte_array() {
+    let message = Message::Ack { id: 7 };
+    let (array, len) = encode_to_byte_array::<_, { Message::MAX_ENCODED_LEN }>(&message).unwrap();
+    assert_eq!(array.as_slice()[..len], encode(&message)[..]);
+    let mut slice = &array.as_slice()[..len];
+    assert_eq!(Message::decode(&mut slice).unwrap(), message);
+}
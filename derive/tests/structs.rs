@@ -0,0 +1,229 @@
+mod common;
+
+use std::{collections::BTreeMap, fmt::Debug, marker::PhantomData};
+
+use codec::{io::ReadError, Codec, Decode, Encode};
+use common::{encode, roundtrip};
+
+#[derive(Codec, Debug, PartialEq)]
+struct Named {
+    id: u32,
+    name: String,
+    flags: (bool, bool),
+}
+
+#[derive(Codec, Debug, PartialEq)]
+struct Tuple(u8, i16, Option<char>);
+
+#[derive(Codec, Debug, PartialEq)]
+struct Unit;
+
+#[derive(Codec, Debug, PartialEq)]
+struct Empty {}
+
+#[derive(Codec, Debug, PartialEq)]
+struct Generic<K: Ord, V> {
+    entries: BTreeMap<K, V>,
+    default: Option<V>,
+}
+
+#[derive(Codec, Debug, PartialEq)]
+struct Marker<T> {
+    value: u16,
+    _marker: PhantomData<T>,
+}
+
+struct NotCodec;
+
+#[derive(Codec, Debug, PartialEq)]
+struct Nested {
+    inner: Named,
+    items: Vec<Tuple>,
+}
+
+/// Only encoded, borrowing everything it writes.
+#[derive(Encode)]
+struct NamedRef<'a> {
+    id: u32,
+    name: &'a str,
+    flags: &'a (bool, bool),
+}
+
+/// Only decoded.
+#[derive(Decode, Debug, PartialEq)]
+struct NamedOwned {
+    id: u32,
+    name: Box<str>,
+    flags: (bool, bool),
+}
+
+#[test]
+fn test_named_struct() {
+    let value = Named {
+        id: 7,
+        name: String::from("ab"),
+        flags: (true, false),
+    };
+    let mut expected = encode(&7u32);
+    expected.extend(encode(&String::from("ab")));
+    expected.extend([1, 0]);
+    assert_eq!(roundtrip(value), expected);
+}
+
+#[test]
+fn test_tuple_struct() {
+    assert_eq!(roundtrip(Tuple(1, -1, None)), [1, 0xFF, 0xFF, 0]);
+}
+
+#[test]
+fn test_unit_structs() {
+    assert!(roundtrip(Unit).is_empty());
+    assert!(roundtrip(Empty {}).is_empty());
+}
+
+#[test]
+fn test_generic_struct() {
+    let mut entries = BTreeMap::new();
+    entries.insert(String::from("k"), 1u64);
+    roundtrip(Generic {
+        entries,
+        default: Some(2u64),
+    });
+}
+
+#[test]
+fn test_bounds_only_for_used_params() {
+    let value: Marker<NotCodec> = Marker {
+        value: 3,
+        _marker: PhantomData,
+    };
+    let bytes = encode(&value);
+    assert_eq!(bytes, [3, 0]);
+    let decoded = Marker::<NotCodec>::decode(&mut &bytes[..]).unwrap();
+    assert_eq!(decoded.value, 3);
+}
+
+#[test]
+fn test_nested_struct() {
+    roundtrip(Nested {
+        inner: Named {
+            id: 1,
+            name: String::new(),
+            flags: (false, true),
+        },
+        items: vec![Tuple(1, 2, Some('x')), Tuple(3, 4, None)],
+    });
+}
+
+#[test]
+fn test_decode_error_propagates() {
+    let bytes = [1u8, 2, 0, 7];
+    assert!(matches!(
+        Tuple::decode(&mut &bytes[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InvalidTag {
+            ty: "Option",
+            tag: 7
+        })
+    ));
+}
+
+#[test]
+fn test_decode_in_place() {
+    let mut value = Nested {
+        inner: Named {
+            id: 1,
+            name: String::with_capacity(32),
+            flags: (false, false),
+        },
+        items: Vec::with_capacity(8),
+    };
+    let name = value.inner.name.as_ptr();
+    let items = value.items.as_ptr();
+    let decoded = Nested {
+        inner: Named {
+            id: 2,
+            name: String::from("reused"),
+            flags: (true, false),
+        },
+        items: vec![Tuple(1, -1, Some('z'))],
+    };
+    value.decode_in_place(&mut &encode(&decoded)[..]).unwrap();
+    assert_eq!(value, decoded);
+    assert_eq!(
+        (value.inner.name.as_ptr(), value.items.as_ptr()),
+        (name, items)
+    );
+
+    let mut unit = Unit;
+    unit.decode_in_place(&mut &[][..]).unwrap();
+    let mut tuple = Tuple(0, 0, None);
+    let error = tuple.decode_in_place(&mut &[1u8, 2][..]).unwrap_err();
+    assert_eq!(
+        error,
+        ReadError::InsufficientData.in_field("1").in_type("Tuple")
+    );
+}
+
+#[test]
+fn test_decode_error_path() {
+    let mut bytes = encode(&Nested {
+        inner: Named {
+            id: 1,
+            name: String::from("ab"),
+            flags: (true, false),
+        },
+        items: vec![Tuple(1, 2, None), Tuple(3, 4, Some('x'))],
+    });
+    let tag = bytes.len() - 5;
+    bytes[tag] = 9;
+    let error = codec::decode::<Nested>(&mut &bytes[..]).unwrap_err();
+    assert_eq!(
+        format!("{:?}", error),
+        "at offset 32 in Nested.items[1].2: invalid tag 9 for Option"
+    );
+    bytes[12] = 0xFF;
+    let error = codec::decode::<Nested>(&mut &bytes[..]).unwrap_err();
+    assert_eq!(
+        format!("{:?}", error),
+        "at offset 14 in Nested.inner.name: invalid utf-8: invalid utf-8 sequence of 1 bytes from index 0"
+    );
+}
+
+#[test]
+fn test_encoded_size() {
+    let value = Nested {
+        inner: Named {
+            id: 1,
+            name: String::from("abc"),
+            flags: (false, true),
+        },
+        items: vec![Tuple(1, -1, None), Tuple(2, 2, Some('z'))],
+    };
+    assert_eq!(value.encoded_size(), encode(&value).len());
+}
+
+#[test]
+fn test_encode_and_decode_only() {
+    let flags = (true, false);
+    let by_ref = NamedRef {
+        id: 4,
+        name: "four",
+        flags: &flags,
+    };
+    let owned = Named {
+        id: 4,
+        name: String::from("four"),
+        flags,
+    };
+    let bytes = encode(&by_ref);
+    assert_eq!(bytes, encode(&owned));
+    assert_eq!(by_ref.encoded_size(), bytes.len());
+    assert_eq!(
+        NamedOwned::decode(&mut &bytes[..]).unwrap(),
+        NamedOwned {
+            id: 4,
+            name: "four".into(),
+            flags,
+        }
+    );
+}
@@ -28,7 +28,7 @@ impl<const N: usize> ByteArray<N> {
 
     #[inline]
     pub const fn as_bytes(&self) -> &ByteSlice {
-        &ByteSlice::from_slice(self.as_slice())
+        ByteSlice::from_slice(self.as_slice())
     }
 
     #[inline]
@@ -140,6 +140,13 @@ impl<const N: usize> ByteArray<N> {
     }
 }
 
+impl<const N: usize> Default for ByteArray<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<const N: usize> PartialEq<&ByteSlice> for ByteArray<N> {
     fn eq(&self, other: &&ByteSlice) -> bool {
         self.0 == other.as_slice()
@@ -12,7 +12,7 @@ use alloc::vec;
 use super::Bytes;
 use crate::bytes::ByteSlice;
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ByteVec(Vec<u8>);
 
 impl ByteVec {
@@ -55,6 +55,11 @@ impl ByteVec {
     pub const fn as_vec(&self) -> &Vec<u8> {
         &self.0
     }
+
+    #[inline]
+    pub fn as_mut_vec(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
 }
 
 impl Deref for ByteVec {
@@ -262,7 +267,7 @@ fn test_from_slice() {
 #[test]
 fn test_from_bytes() {
     let byte_slice = ByteSlice::from_slice(&[1, 2, 3]);
-    let byte_vec = ByteVec::from_bytes(&byte_slice);
+    let byte_vec = ByteVec::from_bytes(byte_slice);
     assert_eq!(byte_vec.as_slice(), byte_slice.as_slice());
 }
 
@@ -0,0 +1,337 @@
+//! Encoding configuration.
+//!
+//! A [`Config`] chooses the byte order of multi-byte values, whether integers are written at
+//! their fixed width or as varints, and how wide the length prefix of variable-length values is.
+//! `Encode` and `Decode` impls read it from the reader or writer via [`Read::config`] and [`Write::config`].
+//!
+//! Configs are carried at the type level by implementing [`ConstConfig`] on a marker type, so
+//! the choice is a constant after monomorphization and the unused branches compile away:
+//!
+//! ```
+//! use codec::config::{Config, ConstConfig, Endian, LengthPrefix};
+//!
+//! struct Wire;
+//!
+//! impl ConstConfig for Wire {
+//!     const CONFIG: Config = Config::DEFAULT
+//!         .with_endian(Endian::Big)
+//!         .with_length_prefix(LengthPrefix::U32);
+//! }
+//!
+//! let mut buf = [0u8; 6];
+//! let mut slice = codec::bytes::ByteSlice::from_slice_mut(&mut buf);
+//! codec::encode_with(&vec![1u8, 2], &mut slice, Wire).unwrap();
+//! assert_eq!(buf, [0, 0, 0, 2, 1, 2]);
+//! ```
+
+use core::marker::PhantomData;
+
+use alloc::vec::Vec;
+
+use crate::io::*;
+
+/// The byte order of multi-byte values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+/// How integers wider than a byte are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IntEncoding {
+    /// At their full width in the configured byte order.
+    #[default]
+    Fixed,
+    /// As LEB128 varints, zigzag encoded if signed. 128-bit integers and floats are always
+    /// written at their fixed width.
+    Varint,
+}
+
+/// How the length of variable-length values such as `Vec` and `String` is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LengthPrefix {
+    U8,
+    U16,
+    U32,
+    #[default]
+    U64,
+    Varint,
+}
+
+/// A complete description of the encoding format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Config {
+    pub endian: Endian,
+    pub int_encoding: IntEncoding,
+    pub length_prefix: LengthPrefix,
+}
+
+impl Config {
+    /// Little-endian fixed-width integers with `u64` length prefixes.
+    pub const DEFAULT: Config = Config {
+        endian: Endian::Little,
+        int_encoding: IntEncoding::Fixed,
+        length_prefix: LengthPrefix::U64,
+    };
+
+    #[inline]
+    pub const fn with_endian(mut self, endian: Endian) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    #[inline]
+    pub const fn with_int_encoding(mut self, int_encoding: IntEncoding) -> Self {
+        self.int_encoding = int_encoding;
+        self
+    }
+
+    #[inline]
+    pub const fn with_length_prefix(mut self, length_prefix: LengthPrefix) -> Self {
+        self.length_prefix = length_prefix;
+        self
+    }
+}
+
+/// A marker type carrying a [`Config`] at the type level.
+pub trait ConstConfig {
+    const CONFIG: Config;
+}
+
+/// Carries [`Config::DEFAULT`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultConfig;
+
+impl ConstConfig for DefaultConfig {
+    const CONFIG: Config = Config::DEFAULT;
+}
+
+/// Wraps a reader or writer so that everything encoded through it uses the config `C`.
+pub struct Configured<'a, IO: ?Sized, C> {
+    io: &'a mut IO,
+    _config: PhantomData<C>,
+}
+
+impl<'a, IO: ?Sized, C: ConstConfig> Configured<'a, IO, C> {
+    #[inline]
+    pub fn new(io: &'a mut IO) -> Self {
+        Configured {
+            io,
+            _config: PhantomData,
+        }
+    }
+}
+
+impl<IO: Read + ?Sized, C: ConstConfig> Read for Configured<'_, IO, C> {
+    #[inline]
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], ReadError> {
+        self.io.read()
+    }
+
+    #[inline]
+    fn read_dynamic(&mut self, n: usize) -> Result<Vec<u8>, ReadError> {
+        self.io.read_dynamic(n)
+    }
+
+    #[inline]
+    fn read_to_end(&mut self) -> Vec<u8> {
+        self.io.read_to_end()
+    }
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8, ReadError> {
+        self.io.read_u8()
+    }
+
+    #[inline]
+    fn read_exact_into(&mut self, buf: &mut crate::bytes::ByteSlice) -> Result<(), ReadError> {
+        self.io.read_exact_into(buf)
+    }
+
+    #[inline]
+    fn skip(&mut self, n: usize) -> Result<(), ReadError> {
+        self.io.skip(n)
+    }
+
+    #[inline]
+    fn remaining_hint(&self) -> Option<usize> {
+        self.io.remaining_hint()
+    }
+
+    #[inline]
+    fn config(&self) -> Config {
+        C::CONFIG
+    }
+
+    #[inline]
+    fn check_len(&mut self, len: usize, elem_size: usize) -> Result<(), ReadError> {
+        self.io.check_len(len, elem_size)
+    }
+
+    #[inline]
+    fn enter(&mut self) -> Result<(), ReadError> {
+        self.io.enter()
+    }
+
+    #[inline]
+    fn leave(&mut self) {
+        self.io.leave()
+    }
+}
+
+impl<'de, IO: BorrowRead<'de> + ?Sized, C: ConstConfig> BorrowRead<'de> for Configured<'_, IO, C> {
+    #[inline]
+    fn read_borrowed(&mut self, n: usize) -> Result<&'de [u8], ReadError> {
+        self.io.read_borrowed(n)
+    }
+}
+
+impl<IO: Write + ?Sized, C: ConstConfig> Write for Configured<'_, IO, C> {
+    #[inline]
+    fn write<'a>(
+        &mut self,
+        data: impl Into<&'a crate::bytes::ByteSlice>,
+    ) -> Result<(), WriteError> {
+        self.io.write(data)
+    }
+
+    #[inline]
+    fn write_u8(&mut self, byte: u8) -> Result<(), WriteError> {
+        self.io.write_u8(byte)
+    }
+
+    #[inline]
+    fn write_all_vectored(&mut self, bufs: &[&crate::bytes::ByteSlice]) -> Result<(), WriteError> {
+        self.io.write_all_vectored(bufs)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), WriteError> {
+        self.io.flush()
+    }
+
+    #[inline]
+    fn config(&self) -> Config {
+        C::CONFIG
+    }
+}
+
+#[cfg(test)]
+use crate::{bytes::ByteSlice, decode_with, encode_with, Codec, Decode, Encode};
+
+#[cfg(test)]
+use alloc::{string::String, vec};
+
+#[cfg(test)]
+fn encode_config<T: Encode, C: ConstConfig>(value: &T, config: C) -> Vec<u8> {
+    let mut buf = [0u8; 256];
+    let mut slice = ByteSlice::from_slice_mut(&mut buf);
+    encode_with(value, &mut slice, config).unwrap();
+    let written = 256 - slice.len();
+    buf[..written].to_vec()
+}
+
+#[cfg(test)]
+fn roundtrip_config<T, C>(value: T, config: C) -> Vec<u8>
+where
+    T: Codec + PartialEq + core::fmt::Debug,
+    C: ConstConfig + Copy,
+{
+    let bytes = encode_config(&value, config);
+    let mut slice = &bytes[..];
+    assert_eq!(decode_with::<T, C>(&mut slice, config).unwrap(), value);
+    assert!(slice.is_empty());
+    bytes
+}
+
+#[cfg(test)]
+#[derive(Clone, Copy)]
+struct BigEndian;
+
+#[cfg(test)]
+impl ConstConfig for BigEndian {
+    const CONFIG: Config = Config::DEFAULT.with_endian(Endian::Big);
+}
+
+#[cfg(test)]
+#[derive(Clone, Copy)]
+struct Compact;
+
+#[cfg(test)]
+impl ConstConfig for Compact {
+    const CONFIG: Config = Config::DEFAULT
+        .with_int_encoding(IntEncoding::Varint)
+        .with_length_prefix(LengthPrefix::Varint);
+}
+
+#[cfg(test)]
+#[derive(Clone, Copy)]
+struct ShortLengths;
+
+#[cfg(test)]
+impl ConstConfig for ShortLengths {
+    const CONFIG: Config = Config::DEFAULT
+        .with_endian(Endian::Big)
+        .with_length_prefix(LengthPrefix::U16);
+}
+
+#[test]
+fn test_default_config_matches_encode() {
+    let value = (0x0102u16, vec![String::from("a")], -1i64);
+    assert_eq!(
+        roundtrip_config(value.clone(), DefaultConfig),
+        crate::impls::encode_bytes(&value)
+    );
+}
+
+#[test]
+fn test_big_endian() {
+    assert_eq!(roundtrip_config(0x0102_0304u32, BigEndian), [1, 2, 3, 4]);
+    assert_eq!(roundtrip_config(-2i16, BigEndian), [0xFF, 0xFE]);
+    assert_eq!(roundtrip_config(1.0f32, BigEndian), [0x3F, 0x80, 0, 0]);
+    assert_eq!(
+        roundtrip_config(vec![1u8], BigEndian),
+        [0, 0, 0, 0, 0, 0, 0, 1, 1]
+    );
+}
+
+#[test]
+fn test_varint_ints() {
+    assert_eq!(roundtrip_config(300u32, Compact), [0xAC, 0x02]);
+    assert_eq!(roundtrip_config(-1i64, Compact), [1]);
+    assert_eq!(roundtrip_config(5usize, Compact), [5]);
+    assert_eq!(roundtrip_config('a', Compact), [0x61]);
+    assert_eq!(roundtrip_config(1u128, Compact).len(), 16);
+    assert_eq!(
+        roundtrip_config(vec![String::from("hi")], Compact),
+        [1, 2, b'h', b'i']
+    );
+    let bytes = encode_config(&70_000u32, Compact);
+    assert!(matches!(
+        decode_with::<u16, _>(&mut &bytes[..], Compact).map_err(ReadError::into_innermost),
+        Err(ReadError::Overflow)
+    ));
+}
+
+#[test]
+fn test_length_prefix() {
+    assert_eq!(roundtrip_config(vec![7u8, 8], ShortLengths), [0, 2, 7, 8]);
+    let too_long = vec![0u8; u16::MAX as usize + 1];
+    let mut buf = vec![0u8; too_long.len() + 8];
+    let mut slice = ByteSlice::from_slice_mut(&mut buf);
+    assert!(matches!(
+        encode_with(&too_long, &mut slice, ShortLengths),
+        Err(WriteError::Overflow)
+    ));
+}
+
+#[test]
+fn test_configured() {
+    let bytes = [0u8, 0, 0, 7];
+    let mut slice = &bytes[..];
+    let mut reader = Configured::<_, BigEndian>::new(&mut slice);
+    assert_eq!(u32::decode(&mut reader).unwrap(), 7);
+    assert_eq!(reader.config(), BigEndian::CONFIG);
+}
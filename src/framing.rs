@@ -0,0 +1,448 @@
+//! Length-delimited framing for sending many messages back to back over one stream.
+//!
+//! Each frame is the length of the encoded message followed by the message itself, so a reader
+//! can always tell where one message ends and the next begins. The length is written as the
+//! [`LengthPrefix`] both sides are configured with, fixed-width prefixes in little-endian order.
+//!
+//! ```
+//! use codec::framing::{FramedReader, FramedWriter};
+//!
+//! let mut writer = FramedWriter::new(Vec::new());
+//! writer.send(&(1u8, String::from("one"))).unwrap();
+//! writer.send(&(2u8, String::from("two"))).unwrap();
+//! let bytes = writer.into_inner();
+//!
+//! let reader = FramedReader::<_, (u8, String)>::new(&bytes[..]);
+//! let messages: Result<Vec<_>, _> = reader.collect();
+//! assert_eq!(messages.unwrap()[1], (2, String::from("two")));
+//! ```
+
+use alloc::vec::Vec;
+
+use core::marker::PhantomData;
+
+use crate::{
+    bytes::ByteSlice,
+    config::LengthPrefix,
+    impls::read_into_vec,
+    io::*,
+    varint::{decode_varint, encode_varint, MAX_VARINT_LEN},
+    Decode, Encode,
+};
+
+/// The largest frame accepted unless configured otherwise: 16 MiB.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Writes each message sent through it as one length-prefixed frame.
+///
+/// Messages are encoded under [`Config::DEFAULT`](crate::config::Config::DEFAULT) into a buffer
+/// that is reused from one message to the next, then written out together with their length in
+/// a single [`Write::write_all_vectored`].
+#[derive(Debug)]
+pub struct FramedWriter<W> {
+    inner: W,
+    length_prefix: LengthPrefix,
+    max_frame_size: usize,
+    buf: Vec<u8>,
+}
+
+impl<W> FramedWriter<W> {
+    /// Frames with `u32` lengths and a limit of [`DEFAULT_MAX_FRAME_SIZE`].
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        FramedWriter {
+            inner,
+            length_prefix: LengthPrefix::U32,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            buf: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn with_length_prefix(mut self, length_prefix: LengthPrefix) -> Self {
+        self.length_prefix = length_prefix;
+        self
+    }
+
+    /// Refuses to send messages that encode to more than `max_frame_size` bytes.
+    #[inline]
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> FramedWriter<W> {
+    /// Encodes `message` and writes it as the next frame.
+    ///
+    /// Fails with [`WriteError::Overflow`], before anything is written, if the message is larger
+    /// than the maximum frame size or than the length prefix can describe.
+    pub fn send<T: Encode + ?Sized>(&mut self, message: &T) -> Result<(), WriteError> {
+        self.buf.clear();
+        message.encode(&mut self.buf)?;
+        if self.buf.len() > self.max_frame_size {
+            return Err(WriteError::Overflow);
+        }
+        let mut header = [0u8; MAX_VARINT_LEN];
+        let mut rest = ByteSlice::from_slice_mut(&mut header);
+        write_frame_len(self.length_prefix, self.buf.len(), &mut rest)?;
+        let header_len = MAX_VARINT_LEN - rest.len();
+        self.inner.write_all_vectored(&[
+            ByteSlice::from_slice(&header[..header_len]),
+            ByteSlice::from_slice(&self.buf),
+        ])
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<(), WriteError> {
+        self.inner.flush()
+    }
+}
+
+/// Reads length-prefixed frames written by a [`FramedWriter`] and decodes each as a `T`.
+///
+/// Every frame must hold exactly one `T`, or decoding it fails with
+/// [`ReadError::TrailingBytes`]. A frame that fails to decode has still been read in full, so
+/// reading can carry on with the next one. Failing to read a frame leaves the stream at an
+/// unknown point, so iteration stops after such an error.
+#[derive(Debug)]
+pub struct FramedReader<R, T> {
+    inner: R,
+    length_prefix: LengthPrefix,
+    max_frame_size: usize,
+    buf: Vec<u8>,
+    broken: bool,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<R, T> FramedReader<R, T> {
+    /// Expects `u32` lengths and refuses frames over [`DEFAULT_MAX_FRAME_SIZE`].
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        FramedReader {
+            inner,
+            length_prefix: LengthPrefix::U32,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            buf: Vec::new(),
+            broken: false,
+            _message: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn with_length_prefix(mut self, length_prefix: LengthPrefix) -> Self {
+        self.length_prefix = length_prefix;
+        self
+    }
+
+    /// Fails with [`ReadError::LimitExceeded`] on frames longer than `max_frame_size`, before
+    /// reading or allocating for them.
+    #[inline]
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, T: Decode> FramedReader<R, T> {
+    /// Reads and decodes the next message, or returns `None` if the input ends cleanly where a
+    /// frame would start.
+    pub fn recv(&mut self) -> Result<Option<T>, ReadError> {
+        if !self.read_frame()? {
+            return Ok(None);
+        }
+        crate::decode_exact(&self.buf).map(Some)
+    }
+
+    /// Like [`recv`](FramedReader::recv), but decodes over `message` with
+    /// [`Decode::decode_in_place`] to reuse its buffers. Returns `false` at the end of the input.
+    pub fn recv_in_place(&mut self, message: &mut T) -> Result<bool, ReadError> {
+        if !self.read_frame()? {
+            return Ok(false);
+        }
+        let mut rest = &self.buf[..];
+        let mut limited = Limited::new(&mut rest, Limits::DEFAULT);
+        message
+            .decode_in_place(&mut limited)
+            .map_err(|e| e.at_offset(limited.bytes_read()))?;
+        match rest.len() {
+            0 => Ok(true),
+            remaining => Err(ReadError::TrailingBytes { remaining }),
+        }
+    }
+
+    /// Reads the next frame into the buffer, returning `false` if there is none.
+    fn read_frame(&mut self) -> Result<bool, ReadError> {
+        let result = self.read_frame_unchecked();
+        self.broken = result.is_err();
+        result
+    }
+
+    fn read_frame_unchecked(&mut self) -> Result<bool, ReadError> {
+        let first = match self.inner.read_u8() {
+            Ok(first) => first,
+            Err(ReadError::InsufficientData) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let len = read_frame_len(self.length_prefix, first, &mut self.inner)?;
+        let len = usize::try_from(len).map_err(|_| ReadError::Overflow)?;
+        if len > self.max_frame_size {
+            return Err(ReadError::LimitExceeded);
+        }
+        self.inner.check_len(len, 1)?;
+        read_into_vec(&mut self.buf, len, &mut self.inner)?;
+        Ok(true)
+    }
+}
+
+impl<R: Read, T: Decode> Iterator for FramedReader<R, T> {
+    type Item = Result<T, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.broken {
+            return None;
+        }
+        self.recv().transpose()
+    }
+}
+
+fn write_frame_len(
+    length_prefix: LengthPrefix,
+    len: usize,
+    io: &mut impl Write,
+) -> Result<(), WriteError> {
+    match length_prefix {
+        LengthPrefix::U8 => io.write_u8(u8::try_from(len).map_err(|_| WriteError::Overflow)?),
+        LengthPrefix::U16 => {
+            let len = u16::try_from(len).map_err(|_| WriteError::Overflow)?;
+            io.write(&len.to_le_bytes())
+        }
+        LengthPrefix::U32 => {
+            let len = u32::try_from(len).map_err(|_| WriteError::Overflow)?;
+            io.write(&len.to_le_bytes())
+        }
+        LengthPrefix::U64 => io.write(&(len as u64).to_le_bytes()),
+        LengthPrefix::Varint => encode_varint(len as u64, io),
+    }
+}
+
+/// Reads the rest of a frame length whose `first` byte has already been read.
+fn read_frame_len(
+    length_prefix: LengthPrefix,
+    first: u8,
+    io: &mut impl Read,
+) -> Result<u64, ReadError> {
+    Ok(match length_prefix {
+        LengthPrefix::U8 => first as u64,
+        LengthPrefix::U16 => {
+            let [second] = io.read()?;
+            u16::from_le_bytes([first, second]) as u64
+        }
+        LengthPrefix::U32 => {
+            let [b, c, d] = io.read()?;
+            u32::from_le_bytes([first, b, c, d]) as u64
+        }
+        LengthPrefix::U64 => {
+            let [b, c, d, e, f, g, h] = io.read()?;
+            u64::from_le_bytes([first, b, c, d, e, f, g, h])
+        }
+        LengthPrefix::Varint if first & 0x80 == 0 => first as u64,
+        LengthPrefix::Varint => {
+            // The rest is itself a varint, holding everything above the first seven bits.
+            let rest = decode_varint(io)?;
+            if rest == 0 {
+                return Err(ReadError::OverlongEncoding);
+            }
+            if rest >> 57 != 0 {
+                return Err(ReadError::Overflow);
+            }
+            (first & 0x7F) as u64 | rest << 7
+        }
+    })
+}
+
+#[cfg(test)]
+use alloc::{
+    string::{String, ToString},
+    vec,
+};
+
+#[cfg(test)]
+use crate::impls::encode_bytes;
+
+#[test]
+fn test_framing_roundtrip() {
+    let mut writer = FramedWriter::new(Vec::new());
+    writer.send(&String::from("first")).unwrap();
+    writer.send(&vec![1u16, 2, 3]).unwrap();
+    writer.send(&String::new()).unwrap();
+    writer.flush().unwrap();
+    let bytes = writer.into_inner();
+    assert_eq!(bytes[..4], 13u32.to_le_bytes());
+    assert_eq!(bytes[4..17], encode_bytes(&String::from("first")));
+
+    let mut reader = FramedReader::<_, String>::new(&bytes[..]);
+    assert_eq!(reader.recv().unwrap().unwrap(), "first");
+    assert_eq!(
+        reader.recv().unwrap_err(),
+        ReadError::TrailingBytes { remaining: 3 }
+    );
+    assert_eq!(reader.recv().unwrap().unwrap(), "");
+    assert_eq!(reader.recv().unwrap(), None);
+    assert!(reader.get_ref().is_empty());
+}
+
+#[test]
+fn test_framing_length_prefixes() {
+    let long = "x".repeat(300);
+    for length_prefix in [
+        LengthPrefix::U16,
+        LengthPrefix::U32,
+        LengthPrefix::U64,
+        LengthPrefix::Varint,
+    ] {
+        let mut writer = FramedWriter::new(Vec::new()).with_length_prefix(length_prefix);
+        writer.send("short").unwrap();
+        writer.send(long.as_str()).unwrap();
+        let bytes = writer.into_inner();
+        let reader = FramedReader::<_, String>::new(&bytes[..]).with_length_prefix(length_prefix);
+        let messages: Vec<String> = reader.map(Result::unwrap).collect();
+        assert_eq!(messages, ["short", long.as_str()]);
+    }
+    let mut writer = FramedWriter::new(Vec::new()).with_length_prefix(LengthPrefix::Varint);
+    writer.send(&7u8).unwrap();
+    assert_eq!(writer.get_ref(), &[1, 7]);
+    let mut writer = FramedWriter::new(Vec::new()).with_length_prefix(LengthPrefix::U8);
+    writer.send(&1u8).unwrap();
+    assert_eq!(writer.send(&long), Err(WriteError::Overflow));
+    assert_eq!(writer.get_ref(), &[1, 1]);
+}
+
+#[test]
+fn test_framing_varint_errors() {
+    let overlong = [0x81u8, 0x00];
+    let mut reader =
+        FramedReader::<_, u8>::new(&overlong[..]).with_length_prefix(LengthPrefix::Varint);
+    assert_eq!(reader.recv(), Err(ReadError::OverlongEncoding));
+    let huge = [0xFFu8, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F];
+    let mut reader = FramedReader::<_, u8>::new(&huge[..]).with_length_prefix(LengthPrefix::Varint);
+    assert_eq!(reader.recv(), Err(ReadError::Overflow));
+}
+
+#[test]
+fn test_framing_max_frame_size() {
+    let mut writer = FramedWriter::new(Vec::new()).with_max_frame_size(8);
+    writer.send(&1u64).unwrap();
+    assert_eq!(writer.send(&(1u64, 2u8)), Err(WriteError::Overflow));
+    let mut writer = FramedWriter::new(Vec::new());
+    writer.send(&[0u8; 9]).unwrap();
+    let bytes = writer.into_inner();
+    let mut reader = FramedReader::<_, [u8; 9]>::new(&bytes[..]).with_max_frame_size(8);
+    assert_eq!(reader.recv(), Err(ReadError::LimitExceeded));
+    assert!(reader.next().is_none());
+
+    let hostile = u32::MAX.to_le_bytes();
+    let mut reader = FramedReader::<_, u8>::new(&hostile[..]).with_max_frame_size(usize::MAX);
+    assert_eq!(reader.recv(), Err(ReadError::InsufficientData));
+}
+
+#[test]
+fn test_framing_truncated() {
+    let mut writer = FramedWriter::new(Vec::new());
+    writer.send(&(5u32, true)).unwrap();
+    let bytes = writer.into_inner();
+    let mut reader = FramedReader::<_, (u32, bool)>::new(&bytes[..bytes.len() - 1]);
+    assert_eq!(reader.next(), Some(Err(ReadError::InsufficientData)));
+    assert_eq!(reader.next(), None);
+    let mut reader = FramedReader::<_, (u32, bool)>::new(&bytes[..2]);
+    assert_eq!(reader.recv(), Err(ReadError::InsufficientData));
+}
+
+#[test]
+fn test_framing_decode_error_keeps_going() {
+    let mut writer = FramedWriter::new(Vec::new());
+    writer.send(&2u8).unwrap();
+    writer.send(&1u8).unwrap();
+    let bytes = writer.into_inner();
+    let results: Vec<_> = FramedReader::<_, bool>::new(&bytes[..]).collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[0].as_ref().unwrap_err().innermost(),
+        &ReadError::InvalidBool(2)
+    );
+    assert_eq!(results[1], Ok(true));
+}
+
+#[test]
+fn test_framing_recv_in_place() {
+    let mut writer = FramedWriter::new(Vec::new());
+    writer
+        .send(&vec![String::from("a"), String::from("b")])
+        .unwrap();
+    writer.send(&vec![String::from("c")]).unwrap();
+    writer.send(&(vec![String::new()], 0u8)).unwrap();
+    let bytes = writer.into_inner();
+    let mut reader = FramedReader::<_, Vec<String>>::new(&bytes[..]);
+    let mut message = Vec::new();
+    assert!(reader.recv_in_place(&mut message).unwrap());
+    assert_eq!(message, ["a", "b"]);
+    let ptr = message.as_ptr();
+    assert!(reader.recv_in_place(&mut message).unwrap());
+    assert_eq!(
+        (message.as_ptr(), &message[..]),
+        (ptr, &[String::from("c")][..])
+    );
+    assert_eq!(
+        reader.recv_in_place(&mut message),
+        Err(ReadError::TrailingBytes { remaining: 1 })
+    );
+    assert!(!reader.recv_in_place(&mut message).unwrap());
+    assert_eq!(message[0].to_string(), "");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_framing_std_streams() {
+    let mut writer = FramedWriter::new(IoWriter::new(std::io::BufWriter::new(Vec::new())))
+        .with_length_prefix(LengthPrefix::Varint);
+    for i in 0..100u32 {
+        writer.send(&(i, i.to_string())).unwrap();
+    }
+    writer.flush().unwrap();
+    let bytes = writer.into_inner().into_inner().into_inner().unwrap();
+    let reader = FramedReader::<_, (u32, String)>::new(IoReader::new(&bytes[..]))
+        .with_length_prefix(LengthPrefix::Varint);
+    let messages: Vec<_> = reader.map(Result::unwrap).collect();
+    assert_eq!(messages.len(), 100);
+    assert_eq!(messages[42], (42, String::from("42")));
+}
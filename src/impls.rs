@@ -0,0 +1,215 @@
+/// Converts an integer or float to bytes in the given [`Endian`](crate::config::Endian) order.
+macro_rules! to_bytes {
+    ($endian:expr, $value:expr) => {
+        match $endian {
+            $crate::config::Endian::Little => $value.to_le_bytes(),
+            $crate::config::Endian::Big => $value.to_be_bytes(),
+        }
+    };
+}
+
+/// Reads a `$ty` from bytes in the given [`Endian`](crate::config::Endian) order.
+macro_rules! from_bytes {
+    ($endian:expr, $ty:ty, $bytes:expr) => {
+        match $endian {
+            $crate::config::Endian::Little => <$ty>::from_le_bytes($bytes),
+            $crate::config::Endian::Big => <$ty>::from_be_bytes($bytes),
+        }
+    };
+}
+
+mod alloc_types;
+mod borrowed;
+mod collections;
+mod compound;
+mod max_encoded_len;
+mod primitives;
+mod slices;
+
+pub(crate) use compound::decode_array;
+
+use alloc::vec::Vec;
+
+use core::mem::size_of;
+
+use crate::{
+    bytes::ByteSlice,
+    config::LengthPrefix,
+    io::*,
+    varint::{decode_varint, encode_varint},
+};
+
+#[cfg(test)]
+use crate::{
+    bytes::ByteArray, decode_exact, encode_to_slice, encode_to_vec, from_bytes, Codec, Decode,
+    Encode,
+};
+
+#[cfg(test)]
+use alloc::{string::String, vec};
+
+/// Writes the length prefix that precedes every variable-length encoding, at the width chosen by
+/// the writer's config.
+#[inline]
+pub(crate) fn encode_len(len: usize, io: &mut impl Write) -> Result<(), WriteError> {
+    let config = io.config();
+    match config.length_prefix {
+        LengthPrefix::U8 => {
+            let len = u8::try_from(len).map_err(|_| WriteError::Overflow)?;
+            io.write(&[len])
+        }
+        LengthPrefix::U16 => {
+            let len = u16::try_from(len).map_err(|_| WriteError::Overflow)?;
+            io.write(&to_bytes!(config.endian, len))
+        }
+        LengthPrefix::U32 => {
+            let len = u32::try_from(len).map_err(|_| WriteError::Overflow)?;
+            io.write(&to_bytes!(config.endian, len))
+        }
+        LengthPrefix::U64 => io.write(&to_bytes!(config.endian, len as u64)),
+        LengthPrefix::Varint => encode_varint(len as u64, io),
+    }
+}
+
+/// The size of the length prefix written by [`encode_len`] under the default config.
+pub(crate) const LEN_SIZE: usize = size_of::<u64>();
+
+/// Reads a length prefix written by [`encode_len`] for a collection of `T`, checking it against
+/// the reader's limits before anything is allocated.
+#[inline]
+pub(crate) fn decode_len<T>(io: &mut impl Read) -> Result<usize, ReadError> {
+    let config = io.config();
+    let len = match config.length_prefix {
+        LengthPrefix::U8 => {
+            let [len] = io.read()?;
+            len as u64
+        }
+        LengthPrefix::U16 => from_bytes!(config.endian, u16, io.read()?) as u64,
+        LengthPrefix::U32 => from_bytes!(config.endian, u32, io.read()?) as u64,
+        LengthPrefix::U64 => from_bytes!(config.endian, u64, io.read()?),
+        LengthPrefix::Varint => decode_varint(io)?,
+    };
+    let len = usize::try_from(len).map_err(|_| ReadError::Overflow)?;
+    io.check_len(len, size_of::<T>())?;
+    Ok(len)
+}
+
+/// The most bytes a decode reserves up front on the strength of a length prefix alone. Beyond
+/// this, collections grow as their elements are actually decoded.
+const MAX_PREALLOC_BYTES: usize = 64 * 1024;
+
+/// How many `T`s to reserve space for when decoding a collection of `len` of them.
+#[inline]
+pub(crate) fn initial_capacity<T>(len: usize) -> usize {
+    len.min(MAX_PREALLOC_BYTES / size_of::<T>().max(1))
+}
+
+/// Decodes `len` values one at a time with `decode`.
+#[inline]
+pub(crate) fn decode_vec<T, R: Read + ?Sized>(
+    len: usize,
+    io: &mut R,
+    mut decode: impl FnMut(&mut R) -> Result<T, ReadError>,
+) -> Result<Vec<T>, ReadError> {
+    let mut vec = Vec::with_capacity(initial_capacity::<T>(len));
+    io.enter()?;
+    for index in 0..len {
+        vec.push(decode(io).map_err(|e| e.at_index(index))?);
+    }
+    io.leave();
+    Ok(vec)
+}
+
+/// Replaces the contents of `vec` with the next `len` bytes, filling the capacity it already has
+/// before growing it, and then only as the bytes actually arrive.
+#[inline]
+pub(crate) fn read_into_vec(
+    vec: &mut Vec<u8>,
+    len: usize,
+    io: &mut impl Read,
+) -> Result<(), ReadError> {
+    vec.clear();
+    while vec.len() < len {
+        let start = vec.len();
+        let end = len.min(start + MAX_PREALLOC_BYTES.max(vec.capacity() - start));
+        vec.resize(end, 0);
+        io.read_exact_into(ByteSlice::from_slice_mut(&mut vec[start..]))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) fn encode_bytes<T: Encode + ?Sized>(value: &T) -> Vec<u8> {
+    encode_to_vec(value).unwrap().into()
+}
+
+#[cfg(test)]
+pub(crate) fn roundtrip<T: Codec + PartialEq + core::fmt::Debug>(value: T) -> Vec<u8> {
+    let bytes = encode_bytes(&value);
+    let mut slice = &bytes[..];
+    let decoded = T::decode(&mut slice).unwrap();
+    assert_eq!(decoded, value);
+    assert!(slice.is_empty());
+    bytes
+}
+
+#[test]
+fn test_encode_to_vec() {
+    let value = (1u16, String::from("grow"), vec![0u8; 10_000]);
+    let bytes = encode_to_vec(&value).unwrap();
+    assert_eq!(bytes.len(), value.encoded_size());
+    assert_eq!(
+        <(u16, String, Vec<u8>)>::decode(&mut bytes.as_slice()).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn test_encode_to_slice() {
+    let mut buf = [0xAAu8; 8];
+    let written = encode_to_slice(&(1u8, 2u16), ByteSlice::from_slice_mut(&mut buf)).unwrap();
+    assert_eq!(written, 3);
+    assert_eq!(buf, [1, 2, 0, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA]);
+    assert!(matches!(
+        encode_to_slice(&1u64, ByteSlice::from_slice_mut(&mut buf[..7])),
+        Err(WriteError::InsufficientSpace)
+    ));
+}
+
+#[test]
+fn test_decode_exact() {
+    let bytes = encode_bytes(&(7u32, String::from("exact")));
+    assert_eq!(
+        decode_exact::<(u32, String)>(&bytes).unwrap(),
+        (7, String::from("exact"))
+    );
+    let mut padded = bytes.clone();
+    padded.extend_from_slice(&[0, 0, 0]);
+    assert_eq!(
+        decode_exact::<(u32, String)>(&padded).unwrap_err(),
+        ReadError::TrailingBytes { remaining: 3 }
+    );
+    assert_eq!(
+        decode_exact::<(u32, String)>(&bytes[..10])
+            .unwrap_err()
+            .innermost(),
+        &ReadError::InsufficientData
+    );
+}
+
+#[test]
+fn test_from_bytes() {
+    let (array, written) = crate::encode_to_byte_array::<_, 4>(&-3i32).unwrap();
+    assert_eq!(written, 4);
+    assert_eq!(from_bytes::<i32>(&array).unwrap(), -3);
+    let vec = encode_to_vec(&Some(true)).unwrap();
+    assert_eq!(from_bytes::<Option<bool>>(&vec).unwrap(), Some(true));
+    assert_eq!(
+        from_bytes::<u8>(&vec).unwrap_err(),
+        ReadError::TrailingBytes { remaining: 1 }
+    );
+    assert_eq!(
+        from_bytes::<u16>(ByteArray::from([1u8, 0]).as_bytes()).unwrap(),
+        1
+    );
+}
@@ -0,0 +1,408 @@
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::VecDeque,
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+
+use super::{decode_len, encode_len, read_into_vec, LEN_SIZE};
+use crate::{bytes::ByteVec, io::*, Decode, Encode};
+
+#[cfg(test)]
+use super::{encode_bytes, roundtrip};
+
+#[cfg(test)]
+use alloc::vec;
+
+/// Encoded as a length prefix followed by each element.
+impl<T: Encode> Encode for Vec<T> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        T::encode_slice(self, io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.iter().map(T::encoded_size).sum::<usize>()
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let len = decode_len::<T>(io)?;
+        T::decode_vec(len, io)
+    }
+
+    #[inline]
+    fn decode_in_place(&mut self, io: &mut impl Read) -> Result<(), ReadError> {
+        let len = decode_len::<T>(io)?;
+        T::decode_vec_in_place(self, len, io)
+    }
+}
+
+impl<T: Encode> Encode for VecDeque<T> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        let (front, back) = self.as_slices();
+        T::encode_slice(front, io)?;
+        T::encode_slice(back, io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.iter().map(T::encoded_size).sum::<usize>()
+    }
+}
+
+impl<T: Decode> Decode for VecDeque<T> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        Vec::decode(io).map(VecDeque::from)
+    }
+}
+
+impl Encode for ByteVec {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        io.write(self.as_bytes())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
+}
+
+impl Decode for ByteVec {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let len = decode_len::<u8>(io)?;
+        io.read_dynamic(len).map(ByteVec::from_vec)
+    }
+
+    #[inline]
+    fn decode_in_place(&mut self, io: &mut impl Read) -> Result<(), ReadError> {
+        let len = decode_len::<u8>(io)?;
+        read_into_vec(self.as_mut_vec(), len, io)
+    }
+}
+
+/// Encoded as a length prefix followed by the UTF-8 bytes, which are validated on decode.
+impl Encode for String {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        io.write(self.as_bytes())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
+}
+
+impl Decode for String {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let len = decode_len::<u8>(io)?;
+        String::from_utf8(io.read_dynamic(len)?).map_err(|e| ReadError::InvalidUtf8(e.utf8_error()))
+    }
+
+    /// Keeps the string's buffer even if decoding fails, leaving it empty.
+    #[inline]
+    fn decode_in_place(&mut self, io: &mut impl Read) -> Result<(), ReadError> {
+        let len = decode_len::<u8>(io)?;
+        let mut bytes = core::mem::take(self).into_bytes();
+        let result = read_into_vec(&mut bytes, len, io).and_then(|()| {
+            core::str::from_utf8(&bytes)
+                .map(|_| ())
+                .map_err(ReadError::InvalidUtf8)
+        });
+        if result.is_err() {
+            bytes.clear();
+        }
+        // Safety: `bytes` has either just been validated as UTF-8 or been cleared
+        *self = unsafe { String::from_utf8_unchecked(bytes) };
+        result
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Box<T> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (**self).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        (**self).encoded_size()
+    }
+}
+
+impl<T: Decode> Decode for Box<T> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        io.enter()?;
+        let value = T::decode(io)?;
+        io.leave();
+        Ok(Box::new(value))
+    }
+}
+
+impl<T: Decode> Decode for Box<[T]> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        Vec::decode(io).map(Vec::into_boxed_slice)
+    }
+}
+
+impl Decode for Box<str> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        String::decode(io).map(String::into_boxed_str)
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Rc<T> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (**self).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        (**self).encoded_size()
+    }
+}
+
+impl<T: Decode> Decode for Rc<T> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        io.enter()?;
+        let value = T::decode(io)?;
+        io.leave();
+        Ok(Rc::new(value))
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Arc<T> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (**self).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        (**self).encoded_size()
+    }
+}
+
+impl<T: Decode> Decode for Arc<T> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        io.enter()?;
+        let value = T::decode(io)?;
+        io.leave();
+        Ok(Arc::new(value))
+    }
+}
+
+/// Encoded the same way as the borrowed form, which for `Cow<str>` and `Cow<[T]>` matches the
+/// owned `String` and `Vec<T>`. Decoding always produces [`Cow::Owned`].
+impl<T: Encode + ToOwned + ?Sized> Encode for Cow<'_, T> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (**self).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        (**self).encoded_size()
+    }
+}
+
+impl<T: ToOwned + ?Sized> Decode for Cow<'_, T>
+where
+    T::Owned: Decode,
+{
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        T::Owned::decode(io).map(Cow::Owned)
+    }
+}
+
+#[test]
+fn test_vec() {
+    assert_eq!(roundtrip(Vec::<u32>::new()), [0; 8]);
+    assert_eq!(
+        roundtrip(vec![1u16, 2]),
+        [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0]
+    );
+    roundtrip(vec![vec![true], vec![], vec![false, true]]);
+}
+
+#[test]
+fn test_byte_vecs() {
+    assert_eq!(
+        roundtrip(vec![7u8, 8, 9]),
+        [3, 0, 0, 0, 0, 0, 0, 0, 7, 8, 9]
+    );
+    assert_eq!(
+        roundtrip(ByteVec::from_slice(&[7, 8, 9])),
+        encode_bytes(&vec![7u8, 8, 9])
+    );
+    let truncated = [4u8, 0, 0, 0, 0, 0, 0, 0, 1, 2];
+    assert!(matches!(
+        Vec::<u8>::decode(&mut &truncated[..]),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_vec_deque() {
+    let mut deque = VecDeque::from(vec![2u32, 3]);
+    deque.push_front(1);
+    let bytes = roundtrip(deque);
+    assert_eq!(bytes, encode_bytes(&vec![1u32, 2, 3]));
+}
+
+#[test]
+fn test_string() {
+    assert_eq!(
+        roundtrip(String::from("hi")),
+        [2, 0, 0, 0, 0, 0, 0, 0, b'h', b'i']
+    );
+    roundtrip(String::new());
+    roundtrip(String::from("crab \u{1F980}"));
+    roundtrip("boxed".to_owned().into_boxed_str());
+}
+
+#[test]
+fn test_invalid_utf8() {
+    let bytes = [2u8, 0, 0, 0, 0, 0, 0, 0, 0xC3, 0x28];
+    let result = String::decode(&mut &bytes[..]);
+    let Err(ReadError::InvalidUtf8(e)) = result else {
+        panic!("expected InvalidUtf8, got {:?}", result);
+    };
+    assert_eq!(e.valid_up_to(), 0);
+}
+
+#[test]
+fn test_smart_pointers() {
+    assert_eq!(roundtrip(Box::new(5u32)), [5, 0, 0, 0]);
+    roundtrip(Rc::new(String::from("rc")));
+    roundtrip(Arc::new(Some(1i64)));
+    let boxed: Box<[u16]> = vec![1, 2, 3].into_boxed_slice();
+    assert_eq!(roundtrip(boxed), encode_bytes(&vec![1u16, 2, 3]));
+}
+
+#[test]
+fn test_cow() {
+    let borrowed: Cow<'_, str> = Cow::Borrowed("cow");
+    let bytes = encode_bytes(&borrowed);
+    assert_eq!(bytes, encode_bytes(&String::from("cow")));
+    let decoded = Cow::<str>::decode(&mut &bytes[..]).unwrap();
+    assert!(matches!(decoded, Cow::Owned(ref s) if s == "cow"));
+    roundtrip(Cow::<[u32]>::Owned(vec![1, 2]));
+}
+
+#[test]
+fn test_array_partial_failure_drops_decoded() {
+    let bytes = encode_bytes(&[String::from("a"), String::from("b")]);
+    assert!(matches!(
+        <[String; 3]>::decode(&mut &bytes[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_encoded_size() {
+    let nested = vec![vec![1u16], vec![], vec![2, 3]];
+    assert_eq!(nested.encoded_size(), encode_bytes(&nested).len());
+    let text = String::from("crab \u{1F980}");
+    assert_eq!(text.encoded_size(), encode_bytes(&text).len());
+    assert_eq!(ByteVec::from_slice(&[1, 2]).encoded_size(), 10);
+    let deque = VecDeque::from(vec![Some(1u8), None]);
+    assert_eq!(deque.encoded_size(), encode_bytes(&deque).len());
+    assert_eq!(Rc::new(5u32).encoded_size(), 4);
+    let cow: Cow<'_, str> = Cow::Borrowed("cow");
+    assert_eq!(cow.encoded_size(), encode_bytes(&cow).len());
+}
+
+#[test]
+fn test_decode_in_place() {
+    let mut text = String::with_capacity(64);
+    let ptr = text.as_ptr();
+    text.decode_in_place(&mut &encode_bytes(&String::from("reuse"))[..])
+        .unwrap();
+    assert_eq!(text, "reuse");
+    assert_eq!(text.as_ptr(), ptr);
+
+    let mut names = vec![String::with_capacity(16), String::from("dropped")];
+    let inner = names[0].as_ptr();
+    let outer = names.as_ptr();
+    let bytes = encode_bytes(&vec![String::from("a")]);
+    names.decode_in_place(&mut &bytes[..]).unwrap();
+    assert_eq!(names, ["a"]);
+    assert_eq!((names.as_ptr(), names[0].as_ptr()), (outer, inner));
+    let bytes = encode_bytes(&vec![String::from("b"), String::from("c")]);
+    names.decode_in_place(&mut &bytes[..]).unwrap();
+    assert_eq!(names, ["b", "c"]);
+
+    let mut raw = Vec::with_capacity(8);
+    raw.extend_from_slice(&[9, 9, 9]);
+    let ptr = raw.as_ptr();
+    raw.decode_in_place(&mut &encode_bytes(&vec![1u8, 2])[..])
+        .unwrap();
+    assert_eq!((raw.as_slice(), raw.as_ptr()), (&[1u8, 2][..], ptr));
+    let mut buffer = ByteVec::from_vec(Vec::with_capacity(8));
+    buffer
+        .decode_in_place(&mut &encode_bytes(&vec![3u8])[..])
+        .unwrap();
+    assert_eq!(buffer.as_slice(), [3]);
+}
+
+#[test]
+fn test_decode_in_place_errors() {
+    let mut text = String::with_capacity(1024);
+    text.push_str("old");
+    let invalid = encode_bytes(&vec![0xFFu8]);
+    assert!(matches!(
+        text.decode_in_place(&mut &invalid[..]),
+        Err(ReadError::InvalidUtf8(_))
+    ));
+    assert_eq!((text.as_str(), text.capacity()), ("", 1024));
+    let truncated = encode_bytes("truncated");
+    assert_eq!(
+        text.decode_in_place(&mut &truncated[..12]).unwrap_err(),
+        ReadError::InsufficientData
+    );
+    assert_eq!((text.as_str(), text.capacity()), ("", 1024));
+    text.decode_in_place(&mut &truncated[..]).unwrap();
+    assert_eq!((text.as_str(), text.capacity()), ("truncated", 1024));
+    let mut numbers = vec![1u32, 2, 3];
+    let mut bytes = encode_bytes(&vec![4u32, 5]);
+    bytes.pop();
+    assert_eq!(
+        numbers.decode_in_place(&mut &bytes[..]).unwrap_err(),
+        ReadError::InsufficientData.at_index(1)
+    );
+    let mut hostile = 1u64 << 40;
+    let mut raw: Vec<u8> = Vec::new();
+    assert_eq!(
+        raw.decode_in_place(&mut &encode_bytes(&hostile)[..])
+            .unwrap_err(),
+        ReadError::InsufficientData
+    );
+    hostile
+        .decode_in_place(&mut &[1u8, 0, 0, 0, 0, 0, 0, 0][..])
+        .unwrap();
+    assert_eq!(hostile, 1);
+}
@@ -0,0 +1,300 @@
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+
+use core::marker::PhantomData;
+
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    bytes::{ByteArray, ByteSlice, ByteVec},
+    impls::decode_len,
+    io::*,
+    varint::VarInt,
+    with::{As, CodecWith},
+    BorrowDecode, Decode,
+};
+
+#[cfg(test)]
+use crate::{
+    config::{Config, Configured, ConstConfig, LengthPrefix},
+    decode_borrowed,
+    impls::encode_bytes,
+};
+
+#[cfg(test)]
+use alloc::vec;
+
+// Each reads the same length prefix and bytes as the owned `Vec<u8>`, `ByteVec` and `String`
+// encodings, so owned and borrowed values can be decoded from one another's output.
+
+impl<'de: 'a, 'a> BorrowDecode<'de> for &'a [u8] {
+    #[inline]
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+        let len = decode_len::<u8>(io)?;
+        io.read_borrowed(len)
+    }
+}
+
+impl<'de: 'a, 'a> BorrowDecode<'de> for &'a ByteSlice {
+    #[inline]
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+        <&[u8]>::borrow_decode(io).map(ByteSlice::from_slice)
+    }
+}
+
+impl<'de: 'a, 'a> BorrowDecode<'de> for &'a str {
+    #[inline]
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+        core::str::from_utf8(<&[u8]>::borrow_decode(io)?).map_err(ReadError::InvalidUtf8)
+    }
+}
+
+/// Types that never borrow, which decode from borrowed input exactly as from any other.
+macro_rules! impl_owned {
+    ($([$($generics:tt)*] $ty:ty $(where [$($bounds:tt)*])?),* $(,)?) => {
+        $(
+            impl<'de, $($generics)*> BorrowDecode<'de> for $ty $(where $($bounds)*)? {
+                #[inline]
+                fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+                    <Self as Decode>::decode(io)
+                }
+
+                #[inline]
+                fn borrow_decode_array<const N: usize>(
+                    io: &mut impl BorrowRead<'de>,
+                ) -> Result<[Self; N], ReadError> {
+                    <Self as Decode>::decode_array(io)
+                }
+
+                #[inline]
+                fn borrow_decode_vec(
+                    len: usize,
+                    io: &mut impl BorrowRead<'de>,
+                ) -> Result<Vec<Self>, ReadError> {
+                    <Self as Decode>::decode_vec(len, io)
+                }
+            }
+        )*
+    };
+}
+
+impl_owned! {
+    [] u8, [] u16, [] u32, [] u64, [] u128, [] usize,
+    [] i8, [] i16, [] i32, [] i64, [] i128, [] isize,
+    [] f32, [] f64, [] bool, [] char, [] (),
+    [] String, [] Box<str>, [] ByteVec,
+    [] VarInt<u8>, [] VarInt<u16>, [] VarInt<u32>, [] VarInt<u64>, [] VarInt<usize>,
+    [] VarInt<i8>, [] VarInt<i16>, [] VarInt<i32>, [] VarInt<i64>, [] VarInt<isize>,
+    [T: ?Sized] PhantomData<T>,
+    [const M: usize] ByteArray<M>,
+    [T: Decode] VecDeque<T>,
+    [T: Decode] Box<[T]>,
+    [T: Decode] Rc<T>,
+    [T: Decode] Arc<T>,
+    [T: ToOwned + ?Sized] Cow<'_, T> where [T::Owned: Decode],
+    [K: Decode + Ord, V: Decode] BTreeMap<K, V>,
+    [T: Decode + Ord] BTreeSet<T>,
+    [T, S: CodecWith<T>] As<T, S>,
+}
+
+#[cfg(feature = "std")]
+impl_owned! {
+    [K: Decode + Ord + Hash, V: Decode, S: BuildHasher + Default] HashMap<K, V, S>,
+    [T: Decode + Ord + Hash, S: BuildHasher + Default] HashSet<T, S>,
+}
+
+// Containers borrow whenever what they contain does, and are encoded exactly like their owned
+// counterparts.
+
+impl<'de, T: BorrowDecode<'de>> BorrowDecode<'de> for Option<T> {
+    #[inline]
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+        match u8::decode(io)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::borrow_decode(io)?)),
+            tag => Err(ReadError::InvalidTag {
+                ty: "Option",
+                tag: tag as u64,
+            }),
+        }
+    }
+}
+
+impl<'de, T: BorrowDecode<'de>, E: BorrowDecode<'de>> BorrowDecode<'de> for Result<T, E> {
+    #[inline]
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+        match u8::decode(io)? {
+            0 => Ok(Ok(T::borrow_decode(io)?)),
+            1 => Ok(Err(E::borrow_decode(io)?)),
+            tag => Err(ReadError::InvalidTag {
+                ty: "Result",
+                tag: tag as u64,
+            }),
+        }
+    }
+}
+
+impl<'de, T: BorrowDecode<'de>> BorrowDecode<'de> for Vec<T> {
+    #[inline]
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+        let len = decode_len::<T>(io)?;
+        T::borrow_decode_vec(len, io)
+    }
+}
+
+impl<'de, T: BorrowDecode<'de>> BorrowDecode<'de> for Box<T> {
+    #[inline]
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+        io.enter()?;
+        let value = T::borrow_decode(io)?;
+        io.leave();
+        Ok(Box::new(value))
+    }
+}
+
+impl<'de, T: BorrowDecode<'de>, const N: usize> BorrowDecode<'de> for [T; N] {
+    #[inline]
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+        T::borrow_decode_array(io)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident)+) => {
+        impl<'de, $($name: BorrowDecode<'de>),+> BorrowDecode<'de> for ($($name,)+) {
+            #[inline]
+            fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError> {
+                Ok(($($name::borrow_decode(io)?,)+))
+            }
+        }
+    };
+}
+
+macro_rules! impl_tuples {
+    () => {};
+    ($first:ident $($rest:ident)*) => {
+        impl_tuples!($($rest)*);
+        impl_tuple!($($rest)* $first);
+    };
+}
+
+impl_tuples!(P O N M L K J I H G F E D C B A);
+
+#[test]
+fn test_borrowed_bytes() {
+    let bytes = encode_bytes(&vec![1u8, 2, 3]);
+    let decoded: &[u8] = decode_borrowed(&mut &bytes[..]).unwrap();
+    assert_eq!(decoded, [1, 2, 3]);
+    assert_eq!(decoded.as_ptr(), bytes[8..].as_ptr());
+    let decoded: &ByteSlice = decode_borrowed(&mut &bytes[..]).unwrap();
+    assert_eq!(*decoded, [1, 2, 3]);
+}
+
+#[test]
+fn test_borrowed_str() {
+    let bytes = encode_bytes(&String::from("hello"));
+    let mut slice = &bytes[..];
+    let decoded: &str = decode_borrowed(&mut slice).unwrap();
+    assert_eq!(decoded, "hello");
+    assert!(slice.is_empty());
+    let mut invalid = encode_bytes(&vec![0xFFu8]);
+    assert!(matches!(
+        decode_borrowed::<&str>(&mut &invalid[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InvalidUtf8(_))
+    ));
+    invalid.pop();
+    assert!(matches!(
+        decode_borrowed::<&str>(&mut &invalid[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_borrowed_owned_mix() {
+    let bytes = encode_bytes(&(7u32, String::from("abc")));
+    let mut slice = &bytes[..];
+    let number: u32 = decode_borrowed(&mut slice).unwrap();
+    let text: &str = decode_borrowed(&mut slice).unwrap();
+    assert_eq!((number, text), (7, "abc"));
+}
+
+#[test]
+fn test_borrowed_limits_and_config() {
+    struct ShortLengths;
+
+    impl ConstConfig for ShortLengths {
+        const CONFIG: Config = Config::DEFAULT.with_length_prefix(LengthPrefix::U8);
+    }
+
+    let bytes = [2u8, b'h', b'i'];
+    let mut slice = &bytes[..];
+    let mut configured = Configured::<_, ShortLengths>::new(&mut slice);
+    assert_eq!(<&str>::borrow_decode(&mut configured).unwrap(), "hi");
+
+    let bytes: Vec<u8> = encode_bytes(&String::from("hello"));
+    let mut slice = &bytes[..];
+    let mut limited = Limited::new(&mut slice, Limits::UNLIMITED.with_max_bytes(12));
+    assert!(matches!(
+        <&str>::borrow_decode(&mut limited),
+        Err(ReadError::LimitExceeded)
+    ));
+}
+
+#[test]
+fn test_borrowed_containers() {
+    let bytes = encode_bytes(&(
+        Some(String::from("some")),
+        vec![String::from("a"), String::from("bc")],
+        [String::from("x"), String::from("y")],
+        Ok::<_, u8>(vec![1u8, 2]),
+    ));
+    let mut slice = &bytes[..];
+    type Borrowed<'a> = (
+        Option<&'a str>,
+        Vec<&'a str>,
+        [&'a str; 2],
+        Result<&'a [u8], u8>,
+    );
+    let (some, list, pair, ok): Borrowed = decode_borrowed(&mut slice).unwrap();
+    assert!(slice.is_empty());
+    assert_eq!(some, Some("some"));
+    assert_eq!(list, ["a", "bc"]);
+    assert_eq!(pair, ["x", "y"]);
+    assert_eq!(ok, Ok(&[1u8, 2][..]));
+    assert_eq!(some.unwrap().as_ptr(), bytes[9..].as_ptr());
+
+    let bytes = encode_bytes(&(None::<String>, vec![7u8; 3], [1u8, 2]));
+    let decoded: (Option<&str>, Box<Vec<u8>>, [u8; 2]) = decode_borrowed(&mut &bytes[..]).unwrap();
+    assert_eq!(decoded, (None, Box::new(vec![7; 3]), [1, 2]));
+    assert_eq!(
+        decode_borrowed::<Option<&str>>(&mut &[2u8][..]).unwrap_err(),
+        ReadError::InvalidTag {
+            ty: "Option",
+            tag: 2
+        }
+        .at_offset(1)
+    );
+    let bytes = encode_bytes(&vec![String::from("ok"), String::from("\u{0}")]);
+    let mut invalid = bytes.clone();
+    let last = invalid.len() - 1;
+    invalid[last] = 0xFF;
+    assert_eq!(
+        decode_borrowed::<Vec<&str>>(&mut &invalid[..])
+            .unwrap_err()
+            .context()
+            .unwrap()
+            .path()
+            .collect::<Vec<_>>(),
+        [&PathSegment::Index(1)]
+    );
+}
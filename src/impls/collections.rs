@@ -0,0 +1,393 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "std")]
+use super::initial_capacity;
+use super::{decode_len, encode_len, LEN_SIZE};
+use crate::{io::*, Decode, Encode};
+
+#[cfg(test)]
+use super::roundtrip;
+
+#[cfg(test)]
+use alloc::{format, string::String};
+
+#[cfg(all(test, feature = "std"))]
+use super::encode_bytes;
+
+/// Encoded as a length prefix followed by each key and value in ascending key order.
+impl<K: Encode, V: Encode> Encode for BTreeMap<K, V> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        for (key, value) in self {
+            key.encode(io)?;
+            value.encode(io)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE
+            + self
+                .iter()
+                .map(|(key, value)| key.encoded_size() + value.encoded_size())
+                .sum::<usize>()
+    }
+}
+
+impl<K: Decode + Ord, V: Decode> Decode for BTreeMap<K, V> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let mut map = BTreeMap::new();
+        map.decode_in_place(io)?;
+        Ok(map)
+    }
+
+    #[inline]
+    fn decode_in_place(&mut self, io: &mut impl Read) -> Result<(), ReadError> {
+        let len = decode_len::<(K, V)>(io)?;
+        self.clear();
+        io.enter()?;
+        for index in 0..len {
+            let key = K::decode(io).map_err(|e| e.at_index(index))?;
+            if self.last_key_value().is_some_and(|(last, _)| *last >= key) {
+                return Err(ReadError::NonCanonicalKeys.at_index(index));
+            }
+            let value = V::decode(io).map_err(|e| e.at_index(index))?;
+            self.insert(key, value);
+        }
+        io.leave();
+        Ok(())
+    }
+}
+
+/// Encoded as a length prefix followed by each item in ascending order.
+impl<T: Encode> Encode for BTreeSet<T> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        for item in self {
+            item.encode(io)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.iter().map(T::encoded_size).sum::<usize>()
+    }
+}
+
+impl<T: Decode + Ord> Decode for BTreeSet<T> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let mut set = BTreeSet::new();
+        set.decode_in_place(io)?;
+        Ok(set)
+    }
+
+    #[inline]
+    fn decode_in_place(&mut self, io: &mut impl Read) -> Result<(), ReadError> {
+        let len = decode_len::<T>(io)?;
+        self.clear();
+        io.enter()?;
+        for index in 0..len {
+            let item = T::decode(io).map_err(|e| e.at_index(index))?;
+            if self.last().is_some_and(|last| *last >= item) {
+                return Err(ReadError::NonCanonicalKeys.at_index(index));
+            }
+            self.insert(item);
+        }
+        io.leave();
+        Ok(())
+    }
+}
+
+/// Encoded exactly like the equivalent [`BTreeMap`]: entries are sorted by key first so that
+/// equal maps always produce identical bytes regardless of hasher state.
+#[cfg(feature = "std")]
+impl<K: Encode + Ord, V: Encode, S> Encode for HashMap<K, V, S> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        let mut entries: Vec<(&K, &V)> = self.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        encode_len(entries.len(), io)?;
+        for (key, value) in entries {
+            key.encode(io)?;
+            value.encode(io)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE
+            + self
+                .iter()
+                .map(|(key, value)| key.encoded_size() + value.encoded_size())
+                .sum::<usize>()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> Decode for HashMap<K, V, S>
+where
+    K: Decode + Ord + Hash,
+    V: Decode,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let mut map = HashMap::with_hasher(S::default());
+        map.decode_in_place(io)?;
+        Ok(map)
+    }
+
+    /// Keeps the map's hasher, and its capacity for the entries.
+    #[inline]
+    fn decode_in_place(&mut self, io: &mut impl Read) -> Result<(), ReadError> {
+        let len = decode_len::<(K, V)>(io)?;
+        self.clear();
+        self.reserve(initial_capacity::<(K, V)>(len));
+        io.enter()?;
+        // Each entry is held back until the next key has been compared with its key.
+        let mut previous: Option<(K, V)> = None;
+        for index in 0..len {
+            let key = K::decode(io).map_err(|e| e.at_index(index))?;
+            if previous
+                .as_ref()
+                .is_some_and(|(previous, _)| *previous >= key)
+            {
+                return Err(ReadError::NonCanonicalKeys.at_index(index));
+            }
+            let value = V::decode(io).map_err(|e| e.at_index(index))?;
+            if let Some((key, value)) = previous.replace((key, value)) {
+                self.insert(key, value);
+            }
+        }
+        if let Some((key, value)) = previous {
+            self.insert(key, value);
+        }
+        io.leave();
+        Ok(())
+    }
+}
+
+/// Encoded exactly like the equivalent [`BTreeSet`]: items are sorted first so that equal sets
+/// always produce identical bytes regardless of hasher state.
+#[cfg(feature = "std")]
+impl<T: Encode + Ord, S> Encode for HashSet<T, S> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        let mut items: Vec<&T> = self.iter().collect();
+        items.sort_unstable();
+        encode_len(items.len(), io)?;
+        for item in items {
+            item.encode(io)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.iter().map(T::encoded_size).sum::<usize>()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> Decode for HashSet<T, S>
+where
+    T: Decode + Ord + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let mut set = HashSet::with_hasher(S::default());
+        set.decode_in_place(io)?;
+        Ok(set)
+    }
+
+    /// Keeps the set's hasher, and its capacity for the items.
+    #[inline]
+    fn decode_in_place(&mut self, io: &mut impl Read) -> Result<(), ReadError> {
+        let len = decode_len::<T>(io)?;
+        self.clear();
+        self.reserve(initial_capacity::<T>(len));
+        io.enter()?;
+        // Each item is held back until the next one has been compared with it.
+        let mut previous: Option<T> = None;
+        for index in 0..len {
+            let item = T::decode(io).map_err(|e| e.at_index(index))?;
+            if previous.as_ref().is_some_and(|previous| *previous >= item) {
+                return Err(ReadError::NonCanonicalKeys.at_index(index));
+            }
+            if let Some(previous) = previous.replace(item) {
+                self.insert(previous);
+            }
+        }
+        if let Some(previous) = previous {
+            self.insert(previous);
+        }
+        io.leave();
+        Ok(())
+    }
+}
+
+#[test]
+fn test_btree_map() {
+    let mut map = BTreeMap::new();
+    map.insert(2u8, true);
+    map.insert(1u8, false);
+    assert_eq!(roundtrip(map), [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 1]);
+    roundtrip(BTreeMap::<u32, u32>::new());
+}
+
+#[test]
+fn test_btree_set() {
+    let set: BTreeSet<u16> = [3, 1, 2].into_iter().collect();
+    assert_eq!(roundtrip(set), [3, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0, 3, 0]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hash_map_matches_btree_map() {
+    let pairs = [(5u32, 'e'), (1, 'a'), (3, 'c'), (4, 'd'), (2, 'b')];
+    let hash_map: HashMap<u32, char> = pairs.into_iter().collect();
+    let btree_map: BTreeMap<u32, char> = pairs.into_iter().collect();
+    assert_eq!(roundtrip(hash_map), encode_bytes(&btree_map));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hash_map_deterministic() {
+    let first: HashMap<u64, u64> = (0..64).map(|i| (i, i * 2)).collect();
+    let second: HashMap<u64, u64> = (0..64).rev().map(|i| (i, i * 2)).collect();
+    assert_eq!(encode_bytes(&first), encode_bytes(&second));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hash_set_matches_btree_set() {
+    let items = [9i8, -3, 0, 4];
+    let hash_set: HashSet<i8> = items.into_iter().collect();
+    let btree_set: BTreeSet<i8> = items.into_iter().collect();
+    assert_eq!(roundtrip(hash_set), encode_bytes(&btree_set));
+}
+
+#[test]
+fn test_encoded_size() {
+    let map: BTreeMap<u8, String> = [(1, "a".into()), (2, "bc".into())].into_iter().collect();
+    assert_eq!(map.encoded_size(), roundtrip(map).len());
+    let set: BTreeSet<u32> = [1, 2, 3].into_iter().collect();
+    assert_eq!(set.encoded_size(), 20);
+}
+
+#[test]
+fn test_decode_in_place() {
+    let mut map = BTreeMap::from([(9u8, String::from("stale"))]);
+    let bytes = super::encode_bytes(&BTreeMap::from([(1u8, String::from("one"))]));
+    map.decode_in_place(&mut &bytes[..]).unwrap();
+    assert_eq!(map, BTreeMap::from([(1, String::from("one"))]));
+    let mut set = BTreeSet::from([5u16]);
+    set.decode_in_place(&mut &super::encode_bytes(&BTreeSet::from([6u16]))[..])
+        .unwrap();
+    assert_eq!(set, BTreeSet::from([6]));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hash_decode_in_place() {
+    let mut map: HashMap<u32, bool> = HashMap::with_capacity(64);
+    map.insert(7, false);
+    let capacity = map.capacity();
+    let bytes = super::encode_bytes(&HashMap::from([(1u32, true), (2, false)]));
+    map.decode_in_place(&mut &bytes[..]).unwrap();
+    assert_eq!(map, HashMap::from([(1, true), (2, false)]));
+    assert_eq!(map.capacity(), capacity);
+    let mut set: HashSet<u8> = HashSet::from([1, 2, 3]);
+    set.decode_in_place(&mut &super::encode_bytes(&HashSet::from([4u8]))[..])
+        .unwrap();
+    assert_eq!(set, HashSet::from([4]));
+}
+
+#[test]
+fn test_btree_rejects_non_canonical() {
+    let duplicate = [2u8, 0, 0, 0, 0, 0, 0, 0, 1, 10, 1, 20];
+    assert_eq!(
+        BTreeMap::<u8, u8>::decode(&mut &duplicate[..])
+            .unwrap_err()
+            .innermost(),
+        &ReadError::NonCanonicalKeys
+    );
+    let descending = [2u8, 0, 0, 0, 0, 0, 0, 0, 2, 20, 1, 10];
+    let error = BTreeMap::<u8, u8>::decode(&mut &descending[..]).unwrap_err();
+    assert_eq!(
+        format!("{:?}", error),
+        "in [1]: keys are duplicated or out of order"
+    );
+    let set = [3u8, 0, 0, 0, 0, 0, 0, 0, 1, 3, 2];
+    assert_eq!(
+        BTreeSet::<u8>::decode(&mut &set[..])
+            .unwrap_err()
+            .innermost(),
+        &ReadError::NonCanonicalKeys
+    );
+    let set = [2u8, 0, 0, 0, 0, 0, 0, 0, 4, 4];
+    assert_eq!(
+        crate::decode_exact::<BTreeSet<u8>>(&set)
+            .unwrap_err()
+            .innermost(),
+        &ReadError::NonCanonicalKeys
+    );
+}
+
+#[test]
+fn test_btree_error_index() {
+    let bad_value = [2u8, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 5];
+    assert_eq!(
+        BTreeMap::<u8, bool>::decode(&mut &bad_value[..]).unwrap_err(),
+        ReadError::InvalidBool(5).at_index(1)
+    );
+    let short = [2u8, 0, 0, 0, 0, 0, 0, 0, 1, 0];
+    assert_eq!(
+        BTreeSet::<u16>::decode(&mut &short[..]).unwrap_err(),
+        ReadError::InsufficientData.at_index(1)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hash_rejects_non_canonical() {
+    let duplicate = [2u8, 0, 0, 0, 0, 0, 0, 0, 1, 10, 1, 20];
+    assert_eq!(
+        HashMap::<u8, u8>::decode(&mut &duplicate[..])
+            .unwrap_err()
+            .innermost(),
+        &ReadError::NonCanonicalKeys
+    );
+    let descending = [2u8, 0, 0, 0, 0, 0, 0, 0, 2, 20, 1, 10];
+    assert_eq!(
+        HashMap::<u8, u8>::decode(&mut &descending[..]).unwrap_err(),
+        ReadError::NonCanonicalKeys.at_index(1)
+    );
+    let set = [3u8, 0, 0, 0, 0, 0, 0, 0, 1, 3, 2];
+    assert_eq!(
+        HashSet::<u8>::decode(&mut &set[..]).unwrap_err(),
+        ReadError::NonCanonicalKeys.at_index(2)
+    );
+    let set = [2u8, 0, 0, 0, 0, 0, 0, 0, 7, 7];
+    assert_eq!(
+        HashSet::<u8>::decode(&mut &set[..])
+            .unwrap_err()
+            .innermost(),
+        &ReadError::NonCanonicalKeys
+    );
+}
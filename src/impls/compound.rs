@@ -0,0 +1,358 @@
+use core::{marker::PhantomData, mem::MaybeUninit};
+
+use crate::{bytes::ByteArray, io::*, Decode, Encode};
+
+#[cfg(test)]
+use super::{encode_bytes, roundtrip};
+
+#[cfg(test)]
+use alloc::{string::String, vec};
+
+impl Encode for () {
+    #[inline]
+    fn encode(&self, _io: &mut impl Write) -> Result<(), WriteError> {
+        Ok(())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        0
+    }
+}
+
+impl Decode for () {
+    #[inline]
+    fn decode(_io: &mut impl Read) -> Result<Self, ReadError> {
+        Ok(())
+    }
+}
+
+impl<T: ?Sized> Encode for PhantomData<T> {
+    #[inline]
+    fn encode(&self, _io: &mut impl Write) -> Result<(), WriteError> {
+        Ok(())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T: ?Sized> Decode for PhantomData<T> {
+    #[inline]
+    fn decode(_io: &mut impl Read) -> Result<Self, ReadError> {
+        Ok(PhantomData)
+    }
+}
+
+/// Encoded exactly like the value it refers to.
+impl<T: Encode + ?Sized> Encode for &T {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (**self).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        (**self).encoded_size()
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: Encode),+> Encode for ($($name,)+) {
+            #[inline]
+            fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+                #[allow(non_snake_case)]
+                let ($($name,)+) = self;
+                $($name.encode(io)?;)+
+                Ok(())
+            }
+
+            #[inline]
+            fn encoded_size(&self) -> usize {
+                #[allow(non_snake_case)]
+                let ($($name,)+) = self;
+                0 $(+ $name.encoded_size())+
+            }
+        }
+
+        impl<$($name: Decode),+> Decode for ($($name,)+) {
+            #[inline]
+            fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+                Ok(($($name::decode(io)?,)+))
+            }
+        }
+    };
+}
+
+macro_rules! impl_tuples {
+    () => {};
+    ($first:ident $($rest:ident)*) => {
+        impl_tuples!($($rest)*);
+        impl_tuple!($($rest)* $first);
+    };
+}
+
+impl_tuples!(P O N M L K J I H G F E D C B A);
+
+/// Decodes `N` values one at a time with `decode`, dropping any already-decoded values if a later
+/// one fails.
+pub(crate) fn decode_array<T, R: Read + ?Sized, const N: usize>(
+    io: &mut R,
+    mut decode: impl FnMut(&mut R) -> Result<T, ReadError>,
+) -> Result<[T; N], ReadError> {
+    struct Guard<T, const N: usize> {
+        array: [MaybeUninit<T>; N],
+        initialized: usize,
+    }
+
+    impl<T, const N: usize> Drop for Guard<T, N> {
+        fn drop(&mut self) {
+            for item in &mut self.array[..self.initialized] {
+                // Safety: the first `initialized` items have been written
+                unsafe { item.assume_init_drop() };
+            }
+        }
+    }
+
+    let mut guard = Guard::<T, N> {
+        array: [const { MaybeUninit::uninit() }; N],
+        initialized: 0,
+    };
+    while guard.initialized < N {
+        let index = guard.initialized;
+        guard.array[index].write(decode(io).map_err(|e| e.at_index(index))?);
+        guard.initialized += 1;
+    }
+    // Safety: every item has been written and `[MaybeUninit<T>; N]` has the same layout as `[T; N]`
+    let array = unsafe { (&guard.array as *const [MaybeUninit<T>; N] as *const [T; N]).read() };
+    core::mem::forget(guard);
+    Ok(array)
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        T::encode_slice(self, io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        self.iter().map(T::encoded_size).sum()
+    }
+}
+
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        T::decode_array(io)
+    }
+}
+
+impl<const N: usize> Encode for ByteArray<N> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        io.write(self.as_bytes())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> Decode for ByteArray<N> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        io.read().map(ByteArray::from)
+    }
+}
+
+/// Encoded as a `0` tag for `None`, or a `1` tag followed by the value for `Some`.
+impl<T: Encode> Encode for Option<T> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        match self {
+            None => 0u8.encode(io),
+            Some(value) => {
+                1u8.encode(io)?;
+                value.encode(io)
+            }
+        }
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        1 + self.as_ref().map_or(0, T::encoded_size)
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        match u8::decode(io)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(io)?)),
+            tag => Err(ReadError::InvalidTag {
+                ty: "Option",
+                tag: tag as u64,
+            }),
+        }
+    }
+}
+
+/// Encoded as a `0` tag followed by the value for `Ok`, or a `1` tag followed by the error for
+/// `Err`.
+impl<T: Encode, E: Encode> Encode for Result<T, E> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        match self {
+            Ok(value) => {
+                0u8.encode(io)?;
+                value.encode(io)
+            }
+            Err(error) => {
+                1u8.encode(io)?;
+                error.encode(io)
+            }
+        }
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        1 + match self {
+            Ok(value) => value.encoded_size(),
+            Err(error) => error.encoded_size(),
+        }
+    }
+}
+
+impl<T: Decode, E: Decode> Decode for Result<T, E> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        match u8::decode(io)? {
+            0 => Ok(Ok(T::decode(io)?)),
+            1 => Ok(Err(E::decode(io)?)),
+            tag => Err(ReadError::InvalidTag {
+                ty: "Result",
+                tag: tag as u64,
+            }),
+        }
+    }
+}
+
+#[test]
+fn test_unit() {
+    assert!(roundtrip(()).is_empty());
+}
+
+#[test]
+fn test_phantom_data() {
+    assert!(roundtrip(PhantomData::<str>).is_empty());
+}
+
+#[test]
+fn test_references() {
+    let value = (1u8, 2u16);
+    assert_eq!(encode_bytes(&&value), encode_bytes(&value));
+    let text = String::from("ref");
+    let pair: (&str, &[u8]) = (&text, &[1, 2]);
+    assert_eq!(
+        encode_bytes(&pair),
+        encode_bytes(&(String::from("ref"), vec![1u8, 2]))
+    );
+    assert_eq!(pair.encoded_size(), 8 + 3 + 8 + 2);
+}
+
+#[test]
+fn test_tuples() {
+    assert_eq!(roundtrip((7u8,)), [7]);
+    assert_eq!(roundtrip((1u8, 2u16, true)), [1, 2, 0, 1]);
+    roundtrip((1u8, -2i32, 'x', 4.5f64, (5u16, false)));
+    let sixteen = (
+        1u8, 2u16, 3u32, 4u64, 5u128, 6i8, 7i16, 8i32, 9i64, 10i128, 11usize, 12isize, true, 'c',
+        1.0f32, 2.0f64,
+    );
+    let bytes = encode_bytes(&sixteen);
+    assert_eq!(
+        bytes.len(),
+        1 + 2 + 4 + 8 + 16 + 1 + 2 + 4 + 8 + 16 + 8 + 8 + 1 + 4 + 4 + 8
+    );
+    fn decode_like<T: Decode>(_: &T, mut bytes: &[u8]) -> T {
+        T::decode(&mut bytes).unwrap()
+    }
+    let decoded = decode_like(&sixteen, &bytes);
+    assert_eq!(decoded.15, 2.0);
+    assert_eq!(encode_bytes(&decoded), bytes);
+}
+
+#[test]
+fn test_arrays() {
+    assert!(roundtrip([0u32; 0]).is_empty());
+    assert_eq!(roundtrip([1u16, 2, 3]), [1, 0, 2, 0, 3, 0]);
+    roundtrip([[1i8, -1], [2, -2]]);
+    roundtrip([Some(1u64), None, Some(3)]);
+}
+
+#[test]
+fn test_byte_arrays() {
+    assert_eq!(roundtrip([1u8, 2, 3, 4]), [1, 2, 3, 4]);
+    assert_eq!(roundtrip(ByteArray::from([9u8, 8, 7])), [9, 8, 7]);
+    assert!(matches!(
+        <[u8; 4]>::decode(&mut &[1u8, 2, 3][..]),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_array_partial_failure() {
+    let bytes = [1u8, 0, 2];
+    assert!(matches!(
+        <[bool; 3]>::decode(&mut &bytes[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InvalidBool(2))
+    ));
+    let bytes = encode_bytes(&[Some(1u32), None]);
+    assert!(matches!(
+        <[Option<u32>; 3]>::decode(&mut &bytes[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_option() {
+    assert_eq!(roundtrip(None::<u32>), [0]);
+    assert_eq!(roundtrip(Some(5u16)), [1, 5, 0]);
+    roundtrip(Some(Some(false)));
+    assert!(matches!(
+        Option::<u8>::decode(&mut &[2u8, 0][..]),
+        Err(ReadError::InvalidTag {
+            ty: "Option",
+            tag: 2
+        })
+    ));
+}
+
+#[test]
+fn test_result() {
+    assert_eq!(roundtrip(Ok::<u8, u16>(3)), [0, 3]);
+    assert_eq!(roundtrip(Err::<u8, u16>(4)), [1, 4, 0]);
+    assert!(matches!(
+        Result::<u8, u8>::decode(&mut &[7u8, 0][..]),
+        Err(ReadError::InvalidTag {
+            ty: "Result",
+            tag: 7
+        })
+    ));
+}
+
+#[test]
+fn test_encoded_size() {
+    assert_eq!(().encoded_size(), 0);
+    let tuple = (1u8, 2u32, Some(3u16), [4u64; 3]);
+    assert_eq!(tuple.encoded_size(), encode_bytes(&tuple).len());
+    let result: Result<u8, Option<u32>> = Err(None);
+    assert_eq!(result.encoded_size(), encode_bytes(&result).len());
+    assert_eq!(ByteArray::<5>::default().encoded_size(), 5);
+}
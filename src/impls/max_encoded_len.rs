@@ -0,0 +1,151 @@
+use core::{marker::PhantomData, mem::size_of};
+
+use crate::{bytes::ByteArray, varint::VarInt, MaxEncodedLen};
+
+#[cfg(test)]
+use crate::{encode_to_byte_array, impls::encode_bytes, Decode};
+
+/// The larger of `a` and `b`, usable in constants.
+const fn max(a: usize, b: usize) -> usize {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// The longest LEB128 encoding of a value with `bits` significant bits.
+const fn max_varint_len(bits: usize) -> usize {
+    bits.div_ceil(7)
+}
+
+macro_rules! impl_fixed {
+    ($($ty:ty),*) => {
+        $(
+            impl MaxEncodedLen for $ty {
+                const MAX_ENCODED_LEN: usize = size_of::<$ty>();
+            }
+        )*
+    };
+}
+
+impl_fixed!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64, bool);
+
+impl MaxEncodedLen for usize {
+    const MAX_ENCODED_LEN: usize = size_of::<u64>();
+}
+
+impl MaxEncodedLen for isize {
+    const MAX_ENCODED_LEN: usize = size_of::<i64>();
+}
+
+impl MaxEncodedLen for char {
+    const MAX_ENCODED_LEN: usize = size_of::<u32>();
+}
+
+macro_rules! impl_varint {
+    ($($ty:ty => $bits:expr),*) => {
+        $(
+            impl MaxEncodedLen for VarInt<$ty> {
+                const MAX_ENCODED_LEN: usize = max_varint_len($bits);
+            }
+        )*
+    };
+}
+
+// Signed values are zigzag encoded into the same number of bits as their unsigned counterparts.
+impl_varint!(
+    u8 => 8, u16 => 16, u32 => 32, u64 => 64, usize => 64,
+    i8 => 8, i16 => 16, i32 => 32, i64 => 64, isize => 64
+);
+
+impl MaxEncodedLen for () {
+    const MAX_ENCODED_LEN: usize = 0;
+}
+
+impl<T: ?Sized> MaxEncodedLen for PhantomData<T> {
+    const MAX_ENCODED_LEN: usize = 0;
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: MaxEncodedLen),+> MaxEncodedLen for ($($name,)+) {
+            const MAX_ENCODED_LEN: usize = 0 $(+ $name::MAX_ENCODED_LEN)+;
+        }
+    };
+}
+
+macro_rules! impl_tuples {
+    () => {};
+    ($first:ident $($rest:ident)*) => {
+        impl_tuples!($($rest)*);
+        impl_tuple!($($rest)* $first);
+    };
+}
+
+impl_tuples!(P O N M L K J I H G F E D C B A);
+
+impl<T: MaxEncodedLen, const N: usize> MaxEncodedLen for [T; N] {
+    const MAX_ENCODED_LEN: usize = T::MAX_ENCODED_LEN * N;
+}
+
+impl<const N: usize> MaxEncodedLen for ByteArray<N> {
+    const MAX_ENCODED_LEN: usize = N;
+}
+
+impl<T: MaxEncodedLen> MaxEncodedLen for Option<T> {
+    const MAX_ENCODED_LEN: usize = 1 + T::MAX_ENCODED_LEN;
+}
+
+impl<T: MaxEncodedLen, E: MaxEncodedLen> MaxEncodedLen for Result<T, E> {
+    const MAX_ENCODED_LEN: usize = 1 + max(T::MAX_ENCODED_LEN, E::MAX_ENCODED_LEN);
+}
+
+#[cfg(test)]
+fn assert_max<T: MaxEncodedLen>(value: &T) {
+    assert!(encode_bytes(value).len() <= T::MAX_ENCODED_LEN);
+}
+
+#[test]
+fn test_primitives() {
+    assert_eq!(u8::MAX_ENCODED_LEN, 1);
+    assert_eq!(i128::MAX_ENCODED_LEN, 16);
+    assert_eq!(usize::MAX_ENCODED_LEN, 8);
+    assert_eq!(char::MAX_ENCODED_LEN, 4);
+    assert_eq!(bool::MAX_ENCODED_LEN, 1);
+    assert_max(&usize::MAX);
+    assert_max(&'\u{10FFFF}');
+}
+
+#[test]
+fn test_varints() {
+    assert_eq!(VarInt::<u8>::MAX_ENCODED_LEN, 2);
+    assert_eq!(VarInt::<u32>::MAX_ENCODED_LEN, 5);
+    assert_eq!(VarInt::<u64>::MAX_ENCODED_LEN, 10);
+    assert_max(&VarInt(u8::MAX));
+    assert_max(&VarInt(u32::MAX));
+    assert_max(&VarInt(i32::MIN));
+    assert_max(&VarInt(u64::MAX));
+    assert_max(&VarInt(i64::MIN));
+}
+
+#[test]
+fn test_compound() {
+    assert_eq!(<(u8, u32, ())>::MAX_ENCODED_LEN, 5);
+    assert_eq!(<[u16; 4]>::MAX_ENCODED_LEN, 8);
+    assert_eq!(<Option<u64>>::MAX_ENCODED_LEN, 9);
+    assert_eq!(<Result<u8, [u8; 3]>>::MAX_ENCODED_LEN, 4);
+    assert_eq!(<ByteArray<7>>::MAX_ENCODED_LEN, 7);
+    assert_max(&(Some(1u32), [Ok::<u8, i64>(1), Err(-1)]));
+}
+
+#[test]
+fn test_encode_to_byte_array() {
+    let (array, len) = encode_to_byte_array::<_, 9>(&Some(0x0102u16)).unwrap();
+    assert_eq!(len, 3);
+    assert_eq!(array.as_slice()[..len], [1, 2, 1]);
+    let (array, len) = encode_to_byte_array::<_, 4>(&None::<u16>).unwrap();
+    assert_eq!(len, 1);
+    let mut slice = &array.as_slice()[..len];
+    assert_eq!(Option::<u16>::decode(&mut slice).unwrap(), None);
+}
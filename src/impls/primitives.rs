@@ -0,0 +1,300 @@
+use core::mem::size_of;
+
+use alloc::vec::Vec;
+
+use super::read_into_vec;
+use crate::{
+    config::IntEncoding,
+    io::*,
+    varint::{decode_varint, encode_varint, zigzag_decode, zigzag_encode},
+    Decode, Encode,
+};
+
+#[cfg(test)]
+use super::{encode_bytes, roundtrip};
+
+macro_rules! impl_fixed_width {
+    ($($ty:ty),*) => {
+        $(
+            impl Encode for $ty {
+                #[inline]
+                fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+                    io.write(&to_bytes!(io.config().endian, self))
+                }
+
+                #[inline]
+                fn encoded_size(&self) -> usize {
+                    size_of::<$ty>()
+                }
+            }
+
+            impl Decode for $ty {
+                #[inline]
+                fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+                    Ok(from_bytes!(io.config().endian, $ty, io.read()?))
+                }
+            }
+        )*
+    };
+}
+
+/// Integers that are written as varints when the config asks for it, zigzag encoded if signed.
+macro_rules! impl_int {
+    ($($ty:ty => $zigzag:expr, $unzigzag:expr);* $(;)?) => {
+        $(
+            impl Encode for $ty {
+                #[inline]
+                fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+                    let config = io.config();
+                    match config.int_encoding {
+                        IntEncoding::Fixed => io.write(&to_bytes!(config.endian, self)),
+                        IntEncoding::Varint => encode_varint($zigzag(*self), io),
+                    }
+                }
+
+                #[inline]
+                fn encoded_size(&self) -> usize {
+                    size_of::<$ty>()
+                }
+            }
+
+            impl Decode for $ty {
+                #[inline]
+                fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+                    let config = io.config();
+                    match config.int_encoding {
+                        IntEncoding::Fixed => Ok(from_bytes!(config.endian, $ty, io.read()?)),
+                        IntEncoding::Varint => <$ty>::try_from($unzigzag(decode_varint(io)?))
+                            .map_err(|_| ReadError::Overflow),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_width!(u128, i8, i128, f32, f64);
+impl_int! {
+    u16 => |v| v as u64, |v| v;
+    u32 => |v| v as u64, |v| v;
+    u64 => |v| v, |v| v;
+    i16 => |v| zigzag_encode(v as i64), zigzag_decode;
+    i32 => |v| zigzag_encode(v as i64), zigzag_decode;
+    i64 => zigzag_encode, zigzag_decode;
+}
+
+impl Encode for u8 {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        io.write(&[*self])
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn encode_slice(slice: &[Self], io: &mut impl Write) -> Result<(), WriteError> {
+        io.write(slice)
+    }
+}
+
+impl Decode for u8 {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let [byte] = io.read()?;
+        Ok(byte)
+    }
+
+    #[inline]
+    fn decode_array<const N: usize>(io: &mut impl Read) -> Result<[Self; N], ReadError> {
+        io.read()
+    }
+
+    #[inline]
+    fn decode_vec(len: usize, io: &mut impl Read) -> Result<Vec<Self>, ReadError> {
+        io.read_dynamic(len)
+    }
+
+    #[inline]
+    fn decode_vec_in_place(
+        vec: &mut Vec<Self>,
+        len: usize,
+        io: &mut impl Read,
+    ) -> Result<(), ReadError> {
+        read_into_vec(vec, len, io)
+    }
+}
+
+/// `usize` is always encoded as a `u64` so that encodings are portable across targets.
+impl Encode for usize {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (*self as u64).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        size_of::<u64>()
+    }
+}
+
+impl Decode for usize {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        usize::try_from(u64::decode(io)?).map_err(|_| ReadError::Overflow)
+    }
+}
+
+/// `isize` is always encoded as an `i64` so that encodings are portable across targets.
+impl Encode for isize {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (*self as i64).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        size_of::<i64>()
+    }
+}
+
+impl Decode for isize {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        isize::try_from(i64::decode(io)?).map_err(|_| ReadError::Overflow)
+    }
+}
+
+impl Encode for bool {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (*self as u8).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        1
+    }
+}
+
+impl Decode for bool {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        match u8::decode(io)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(ReadError::InvalidBool(byte)),
+        }
+    }
+}
+
+impl Encode for char {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        (*self as u32).encode(io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        size_of::<u32>()
+    }
+}
+
+impl Decode for char {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        let value = u32::decode(io)?;
+        char::from_u32(value).ok_or(ReadError::InvalidChar(value))
+    }
+}
+
+#[test]
+fn test_unsigned_roundtrip() {
+    roundtrip(0u8);
+    roundtrip(u8::MAX);
+    roundtrip(0xBEEFu16);
+    roundtrip(u32::MAX);
+    roundtrip(0x0123_4567_89AB_CDEFu64);
+    roundtrip(u128::MAX - 7);
+    roundtrip(usize::MAX);
+}
+
+#[test]
+fn test_signed_roundtrip() {
+    roundtrip(i8::MIN);
+    roundtrip(-2i16);
+    roundtrip(i32::MIN);
+    roundtrip(i64::MAX);
+    roundtrip(-123_456_789_012_345_678i128);
+    roundtrip(isize::MIN);
+}
+
+#[test]
+fn test_float_roundtrip() {
+    roundtrip(1.5f32);
+    roundtrip(-0.0f64);
+    roundtrip(f64::INFINITY);
+    let bytes = encode_bytes(&f32::NAN);
+    let decoded = f32::decode(&mut &bytes[..]).unwrap();
+    assert!(decoded.is_nan());
+}
+
+#[test]
+fn test_little_endian() {
+    assert_eq!(roundtrip(0x0102_0304u32), [4, 3, 2, 1]);
+    assert_eq!(roundtrip(-2i16), [0xFE, 0xFF]);
+    assert_eq!(roundtrip(1.0f32), [0, 0, 0x80, 0x3F]);
+}
+
+#[test]
+fn test_usize_is_u64() {
+    assert_eq!(roundtrip(5usize), [5, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(roundtrip(-1isize), [0xFF; 8]);
+}
+
+#[test]
+fn test_bool() {
+    assert_eq!(roundtrip(false), [0]);
+    assert_eq!(roundtrip(true), [1]);
+    assert!(matches!(
+        bool::decode(&mut &[2u8][..]),
+        Err(ReadError::InvalidBool(2))
+    ));
+}
+
+#[test]
+fn test_char() {
+    assert_eq!(roundtrip('a'), [0x61, 0, 0, 0]);
+    roundtrip('\u{1F980}');
+    let surrogate = 0xD800u32.to_le_bytes();
+    assert!(matches!(
+        char::decode(&mut &surrogate[..]),
+        Err(ReadError::InvalidChar(0xD800))
+    ));
+    let too_large = 0x11_0000u32.to_le_bytes();
+    assert!(matches!(
+        char::decode(&mut &too_large[..]),
+        Err(ReadError::InvalidChar(0x11_0000))
+    ));
+}
+
+#[test]
+fn test_insufficient_data() {
+    assert!(matches!(
+        u32::decode(&mut &[1u8, 2, 3][..]),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_encoded_size() {
+    assert_eq!(0u8.encoded_size(), 1);
+    assert_eq!(0u16.encoded_size(), 2);
+    assert_eq!(0i128.encoded_size(), 16);
+    assert_eq!(0f64.encoded_size(), 8);
+    assert_eq!(usize::MAX.encoded_size(), encode_bytes(&usize::MAX).len());
+    assert_eq!(true.encoded_size(), 1);
+    assert_eq!('x'.encoded_size(), encode_bytes(&'x').len());
+}
@@ -0,0 +1,79 @@
+use super::{encode_len, LEN_SIZE};
+use crate::{bytes::ByteSlice, io::*, Encode};
+
+#[cfg(test)]
+use super::encode_bytes;
+
+#[cfg(test)]
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+
+// Unsized types can only be encoded. Each matches its owned counterpart (`Vec<T>`, `String` and
+// `ByteVec`), which is also what decodes them.
+
+/// Encoded as a length prefix followed by each element.
+impl<T: Encode> Encode for [T] {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        T::encode_slice(self, io)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.iter().map(T::encoded_size).sum::<usize>()
+    }
+}
+
+/// Encoded as a length prefix followed by the UTF-8 bytes.
+impl Encode for str {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        io.write(self.as_bytes())
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
+}
+
+impl Encode for ByteSlice {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        encode_len(self.len(), io)?;
+        io.write(self)
+    }
+
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
+}
+
+#[test]
+fn test_slice_matches_vec() {
+    let values = vec![1u32, 2, 3];
+    assert_eq!(encode_bytes(&values[..]), encode_bytes(&values));
+    assert_eq!(
+        encode_bytes(&[true, false][..]),
+        encode_bytes(&vec![true, false])
+    );
+    let boxed: Box<[u16]> = vec![4, 5].into_boxed_slice();
+    assert_eq!(encode_bytes(&boxed), encode_bytes(&vec![4u16, 5]));
+}
+
+#[test]
+fn test_str_matches_string() {
+    assert_eq!(encode_bytes("hello"), encode_bytes(&String::from("hello")));
+    assert_eq!("hello".encoded_size(), 13);
+}
+
+#[test]
+fn test_byte_slice_matches_vec() {
+    let slice = ByteSlice::from_slice(&[7, 8]);
+    assert_eq!(encode_bytes(slice), encode_bytes(&vec![7u8, 8]));
+    assert_eq!(slice.encoded_size(), 10);
+    let bytes: Vec<u8> = encode_bytes(slice);
+    assert_eq!(bytes.len(), 10);
+}
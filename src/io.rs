@@ -4,49 +4,205 @@ extern crate alloc;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
-use core::fmt::{Debug, Formatter};
+use alloc::{boxed::Box, sync::Arc};
 
-use crate::bytes::ByteSlice;
+use core::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+};
 
+mod context;
+mod cursor;
+mod limits;
+mod size_counter;
+#[cfg(feature = "std")]
+mod std_io;
+
+pub use context::*;
+pub use cursor::*;
+pub use limits::*;
+pub use size_counter::*;
+#[cfg(feature = "std")]
+pub use std_io::*;
+
+use crate::{
+    bytes::{ByteSlice, ByteVec},
+    config::Config,
+};
+
+#[derive(Clone)]
 pub enum ReadError {
     InsufficientData,
+    InvalidBool(u8),
+    InvalidChar(u32),
+    InvalidTag {
+        ty: &'static str,
+        tag: u64,
+    },
+    InvalidUtf8(core::str::Utf8Error),
+    Overflow,
+    OverlongEncoding,
+    LimitExceeded,
+    DepthLimitExceeded,
+    /// The keys of a map or set were not in strictly ascending order, or repeated a key. Only
+    /// the canonical encoding of a map or set is accepted.
+    NonCanonicalKeys,
+    /// A whole input was expected to hold exactly one value, but `remaining` bytes were left over.
+    TrailingBytes {
+        remaining: usize,
+    },
+    /// A failure reported by a hand-written impl. See [`ReadError::custom`] and
+    /// [`ReadError::other`].
+    Custom(CustomError),
+    /// An error together with where in the input and in the decoded value it occurred. Use
+    /// [`ReadError::innermost`] to see past it.
+    Context(Box<ErrorContext>),
+    /// Shared so that the error can be cloned. Compares equal only to clones of itself.
     #[cfg(feature = "std")]
-    IoError(std::io::Error),
+    IoError(Arc<std::io::Error>),
+}
+
+impl ReadError {
+    #[inline]
+    pub const fn custom(message: &'static str) -> Self {
+        ReadError::Custom(CustomError::Message(message))
+    }
+
+    #[inline]
+    pub fn other(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        ReadError::Custom(CustomError::other(error))
+    }
 }
 
 #[cfg(feature = "std")]
 impl From<std::io::Error> for ReadError {
     fn from(e: std::io::Error) -> Self {
-        ReadError::IoError(e)
+        ReadError::IoError(Arc::new(e))
     }
 }
 
-impl Debug for ReadError {
+impl Display for ReadError {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             ReadError::InsufficientData => {
                 write!(f, "there is not enough data to complete the requested read")
             }
+            ReadError::InvalidBool(byte) => write!(f, "invalid bool byte: {:#04x}", byte),
+            ReadError::InvalidChar(value) => write!(f, "invalid char scalar value: {:#x}", value),
+            ReadError::InvalidTag { ty, tag } => write!(f, "invalid tag {} for {}", tag, ty),
+            ReadError::InvalidUtf8(e) => write!(f, "invalid utf-8: {}", e),
+            ReadError::Overflow => {
+                write!(f, "decoded value does not fit in the target type")
+            }
+            ReadError::OverlongEncoding => {
+                write!(f, "varint is padded with redundant zero bytes")
+            }
+            ReadError::LimitExceeded => write!(f, "decode limits exceeded"),
+            ReadError::DepthLimitExceeded => write!(f, "maximum nesting depth exceeded"),
+            ReadError::NonCanonicalKeys => write!(f, "keys are duplicated or out of order"),
+            ReadError::TrailingBytes { remaining } => {
+                write!(f, "{} unread bytes after the end of the value", remaining)
+            }
+            ReadError::Custom(e) => write!(f, "{}", e),
+            ReadError::Context(context) => {
+                let mut separator = "";
+                if let Some(offset) = context.offset() {
+                    write!(f, "at offset {}", offset)?;
+                    separator = " ";
+                }
+                if context.path().next().is_some() {
+                    write!(f, "{}in {}", separator, context)?;
+                }
+                write!(f, ": {}", context.error())
+            }
             #[cfg(feature = "std")]
             ReadError::IoError(e) => write!(f, "{}", e),
         }
     }
 }
 
+impl Debug for ReadError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::InvalidUtf8(e) => Some(e),
+            ReadError::Custom(e) => e.source(),
+            ReadError::Context(context) => context.error().source(),
+            #[cfg(feature = "std")]
+            ReadError::IoError(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for ReadError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ReadError::InvalidBool(a), ReadError::InvalidBool(b)) => a == b,
+            (ReadError::InvalidChar(a), ReadError::InvalidChar(b)) => a == b,
+            (
+                ReadError::InvalidTag { ty, tag },
+                ReadError::InvalidTag {
+                    ty: other_ty,
+                    tag: other_tag,
+                },
+            ) => ty == other_ty && tag == other_tag,
+            (ReadError::InvalidUtf8(a), ReadError::InvalidUtf8(b)) => a == b,
+            (
+                ReadError::TrailingBytes { remaining },
+                ReadError::TrailingBytes {
+                    remaining: other_remaining,
+                },
+            ) => remaining == other_remaining,
+            (ReadError::Custom(a), ReadError::Custom(b)) => a == b,
+            (ReadError::Context(a), ReadError::Context(b)) => a == b,
+            #[cfg(feature = "std")]
+            (ReadError::IoError(a), ReadError::IoError(b)) => Arc::ptr_eq(a, b),
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for ReadError {}
+
+#[derive(Clone)]
 pub enum WriteError {
     InsufficientSpace,
+    Overflow,
+    /// A failure reported by a hand-written impl. See [`WriteError::custom`] and
+    /// [`WriteError::other`].
+    Custom(CustomError),
+    /// Shared so that the error can be cloned. Compares equal only to clones of itself.
     #[cfg(feature = "std")]
-    IoError(std::io::Error),
+    IoError(Arc<std::io::Error>),
+}
+
+impl WriteError {
+    #[inline]
+    pub const fn custom(message: &'static str) -> Self {
+        WriteError::Custom(CustomError::Message(message))
+    }
+
+    #[inline]
+    pub fn other(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        WriteError::Custom(CustomError::other(error))
+    }
 }
 
 #[cfg(feature = "std")]
 impl From<std::io::Error> for WriteError {
     fn from(e: std::io::Error) -> Self {
-        WriteError::IoError(e)
+        WriteError::IoError(Arc::new(e))
     }
 }
 
-impl Debug for WriteError {
+impl Display for WriteError {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             WriteError::InsufficientSpace => {
@@ -55,16 +211,168 @@ impl Debug for WriteError {
                     "there is not enough space to complete the requested write"
                 )
             }
+            WriteError::Overflow => {
+                write!(f, "value does not fit in the configured encoding")
+            }
+            WriteError::Custom(e) => write!(f, "{}", e),
             #[cfg(feature = "std")]
             WriteError::IoError(e) => write!(f, "{}", e),
         }
     }
 }
 
+impl Debug for WriteError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Error for WriteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WriteError::Custom(e) => e.source(),
+            #[cfg(feature = "std")]
+            WriteError::IoError(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for WriteError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WriteError::Custom(a), WriteError::Custom(b)) => a == b,
+            #[cfg(feature = "std")]
+            (WriteError::IoError(a), WriteError::IoError(b)) => Arc::ptr_eq(a, b),
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for WriteError {}
+
+/// The payload of [`ReadError::Custom`] and [`WriteError::Custom`].
+#[derive(Clone)]
+pub enum CustomError {
+    Message(&'static str),
+    /// Shared so that the error can be cloned. Compares equal only to clones of itself.
+    Error(Arc<dyn Error + Send + Sync>),
+}
+
+impl CustomError {
+    #[inline]
+    pub fn other(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        CustomError::Error(Arc::from(error.into()))
+    }
+
+    /// The wrapped error, if there is one.
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CustomError::Message(_) => None,
+            CustomError::Error(e) => Some(&**e),
+        }
+    }
+}
+
+impl Display for CustomError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CustomError::Message(message) => write!(f, "{}", message),
+            CustomError::Error(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Debug for CustomError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl PartialEq for CustomError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CustomError::Message(a), CustomError::Message(b)) => a == b,
+            (CustomError::Error(a), CustomError::Error(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for CustomError {}
+
+/// How many bytes the default [`Read::read_exact_into`] and [`Read::skip`] move at a time.
+const CHUNK: usize = 64;
+
 pub trait Read {
     fn read<const N: usize>(&mut self) -> Result<[u8; N], ReadError>;
     fn read_dynamic(&mut self, n: usize) -> Result<Vec<u8>, ReadError>;
     fn read_to_end(&mut self) -> Vec<u8>;
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8, ReadError> {
+        let [byte] = self.read()?;
+        Ok(byte)
+    }
+
+    /// Fills `buf` with the next `buf.len()` bytes without allocating.
+    fn read_exact_into(&mut self, buf: &mut ByteSlice) -> Result<(), ReadError> {
+        let mut chunks = buf.chunks_exact_mut(CHUNK);
+        for chunk in &mut chunks {
+            chunk.copy_from_slice(&self.read::<CHUNK>()?);
+        }
+        for byte in chunks.into_remainder() {
+            *byte = self.read_u8()?;
+        }
+        Ok(())
+    }
+
+    /// Moves past the next `n` bytes without keeping them.
+    fn skip(&mut self, n: usize) -> Result<(), ReadError> {
+        for _ in 0..n / CHUNK {
+            self.read::<CHUNK>()?;
+        }
+        for _ in 0..n % CHUNK {
+            self.read_u8()?;
+        }
+        Ok(())
+    }
+
+    /// How many bytes are left to read, if the reader knows.
+    #[inline]
+    fn remaining_hint(&self) -> Option<usize> {
+        None
+    }
+
+    /// The encoding configuration that values read from here should follow.
+    #[inline]
+    fn config(&self) -> Config {
+        Config::DEFAULT
+    }
+
+    /// Called before decoding a collection of `len` elements of `elem_size` bytes each, so that
+    /// readers enforcing [`Limits`] can refuse it before anything is allocated.
+    #[inline]
+    fn check_len(&mut self, len: usize, elem_size: usize) -> Result<(), ReadError> {
+        let _ = (len, elem_size);
+        Ok(())
+    }
+
+    /// Called before decoding the contents of a value that may nest, such as a derived type, a
+    /// `Box` or a collection, so that readers enforcing [`Limits::max_depth`] can stop runaway
+    /// recursion. Pair with [`leave`](Read::leave) once the contents are decoded; decoding is
+    /// abandoned on error, so error paths need not call it.
+    #[inline]
+    fn enter(&mut self) -> Result<(), ReadError> {
+        Ok(())
+    }
+
+    /// Ends a level of nesting started by [`enter`](Read::enter).
+    #[inline]
+    fn leave(&mut self) {}
 }
 
 impl Read for &[u8] {
@@ -90,10 +398,68 @@ impl Read for &[u8] {
         *self = &[];
         ret
     }
+
+    #[inline]
+    fn read_exact_into(&mut self, buf: &mut ByteSlice) -> Result<(), ReadError> {
+        let data = self.read_borrowed(buf.len())?;
+        buf.copy_from_slice(data);
+        Ok(())
+    }
+
+    #[inline]
+    fn skip(&mut self, n: usize) -> Result<(), ReadError> {
+        self.read_borrowed(n).map(|_| ())
+    }
+
+    #[inline]
+    fn remaining_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+/// A [`Read`] over input that outlives the decode, so that decoded values can borrow from it
+/// instead of copying. See [`BorrowDecode`](crate::BorrowDecode).
+pub trait BorrowRead<'de>: Read {
+    /// Reads the next `n` bytes without copying them.
+    fn read_borrowed(&mut self, n: usize) -> Result<&'de [u8], ReadError>;
+}
+
+impl<'de> BorrowRead<'de> for &'de [u8] {
+    fn read_borrowed(&mut self, n: usize) -> Result<&'de [u8], ReadError> {
+        if n > self.len() {
+            return Err(ReadError::InsufficientData);
+        }
+        let (ret, rest) = self.split_at(n);
+        *self = rest;
+        Ok(ret)
+    }
 }
 
 pub trait Write {
     fn write<'a>(&mut self, data: impl Into<&'a ByteSlice>) -> Result<(), WriteError>;
+
+    #[inline]
+    fn write_u8(&mut self, byte: u8) -> Result<(), WriteError> {
+        self.write(&[byte])
+    }
+
+    /// Writes each of `bufs` in turn, in as few underlying writes as the writer allows.
+    #[inline]
+    fn write_all_vectored(&mut self, bufs: &[&ByteSlice]) -> Result<(), WriteError> {
+        bufs.iter().try_for_each(|buf| self.write(*buf))
+    }
+
+    /// Pushes out anything the writer has buffered.
+    #[inline]
+    fn flush(&mut self) -> Result<(), WriteError> {
+        Ok(())
+    }
+
+    /// The encoding configuration that values written here should follow.
+    #[inline]
+    fn config(&self) -> Config {
+        Config::DEFAULT
+    }
 }
 
 impl Write for &mut ByteSlice {
@@ -109,6 +475,39 @@ impl Write for &mut ByteSlice {
     }
 }
 
+/// Appends to the end of the vector, growing it as needed.
+impl Write for Vec<u8> {
+    #[inline]
+    fn write<'a>(&mut self, data: impl Into<&'a ByteSlice>) -> Result<(), WriteError> {
+        self.extend_from_slice(data.into());
+        Ok(())
+    }
+
+    #[inline]
+    fn write_all_vectored(&mut self, bufs: &[&ByteSlice]) -> Result<(), WriteError> {
+        self.reserve(bufs.iter().map(|buf| buf.len()).sum());
+        bufs.iter().for_each(|buf| self.extend_from_slice(buf));
+        Ok(())
+    }
+}
+
+/// Appends to the end of the buffer, growing it as needed.
+impl Write for ByteVec {
+    #[inline]
+    fn write<'a>(&mut self, data: impl Into<&'a ByteSlice>) -> Result<(), WriteError> {
+        self.as_mut_vec().extend_from_slice(data.into());
+        Ok(())
+    }
+
+    #[inline]
+    fn write_all_vectored(&mut self, bufs: &[&ByteSlice]) -> Result<(), WriteError> {
+        self.as_mut_vec().write_all_vectored(bufs)
+    }
+}
+
+#[cfg(test)]
+use alloc::{format, string::ToString, vec};
+
 #[test]
 fn test_read() {
     let buf = [1, 2, 3, 4, 5];
@@ -146,6 +545,69 @@ fn test_read_to_end() {
     assert_eq!(slice, &[]);
 }
 
+#[test]
+fn test_read_borrowed() {
+    let buf = [1, 2, 3];
+    let mut slice = &buf[..];
+    let data = slice.read_borrowed(2).unwrap();
+    assert_eq!(data, &[1, 2]);
+    assert_eq!(data.as_ptr(), buf.as_ptr());
+    assert_eq!(slice, &[3]);
+    assert!(slice.read_borrowed(2).is_err());
+}
+
+#[test]
+fn test_read_helpers() {
+    let buf: Vec<u8> = (0..200).collect();
+    let mut slice = &buf[..];
+    assert_eq!(slice.remaining_hint(), Some(200));
+    assert_eq!(slice.read_u8().unwrap(), 0);
+    let mut into = [0u8; 3];
+    slice
+        .read_exact_into(ByteSlice::from_slice_mut(&mut into))
+        .unwrap();
+    assert_eq!(into, [1, 2, 3]);
+    slice.skip(190).unwrap();
+    assert_eq!(slice, &[194, 195, 196, 197, 198, 199]);
+    assert!(matches!(slice.skip(7), Err(ReadError::InsufficientData)));
+    assert_eq!(slice.remaining_hint(), Some(6));
+}
+
+/// Implements only the required methods, to exercise the defaults.
+#[cfg(test)]
+struct Plain<'a>(&'a [u8]);
+
+#[cfg(test)]
+impl Read for Plain<'_> {
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], ReadError> {
+        self.0.read()
+    }
+
+    fn read_dynamic(&mut self, _n: usize) -> Result<Vec<u8>, ReadError> {
+        unreachable!("the defaults should not allocate")
+    }
+
+    fn read_to_end(&mut self) -> Vec<u8> {
+        self.0.read_to_end()
+    }
+}
+
+#[test]
+fn test_read_defaults() {
+    let buf: Vec<u8> = (0..=255).collect();
+    let mut reader = Plain(&buf);
+    assert_eq!(reader.remaining_hint(), None);
+    assert_eq!(reader.read_u8().unwrap(), 0);
+    let mut into = [0u8; 100];
+    reader
+        .read_exact_into(ByteSlice::from_slice_mut(&mut into))
+        .unwrap();
+    assert_eq!(into[..], buf[1..101]);
+    reader.skip(150).unwrap();
+    assert_eq!(reader.0, &[251, 252, 253, 254, 255]);
+    assert!(reader.skip(6).is_err());
+}
+
 #[test]
 fn test_write() {
     let mut buf = [0; 5];
@@ -157,3 +619,111 @@ fn test_write() {
     slice.write(&[5]).unwrap();
     assert_eq!(buf, [1, 2, 3, 4, 5]);
 }
+
+#[test]
+fn test_write_vec() {
+    let mut vec = vec![0u8];
+    vec.write(&[1, 2]).unwrap();
+    vec.write(&[3]).unwrap();
+    assert_eq!(vec, [0, 1, 2, 3]);
+    let mut bytes = ByteVec::new();
+    bytes.write(&[4, 5]).unwrap();
+    bytes.write(&[]).unwrap();
+    assert_eq!(bytes.as_slice(), [4, 5]);
+}
+
+#[test]
+fn test_write_helpers() {
+    let parts = [ByteSlice::from_slice(&[2, 3]), ByteSlice::from_slice(&[4])];
+    let mut buf = [0u8; 5];
+    let mut slice = ByteSlice::from_slice_mut(&mut buf);
+    slice.write_u8(1).unwrap();
+    slice.write_all_vectored(&parts).unwrap();
+    slice.flush().unwrap();
+    assert!(matches!(
+        slice.write_all_vectored(&parts),
+        Err(WriteError::InsufficientSpace)
+    ));
+    assert_eq!(buf, [1, 2, 3, 4, 0]);
+    let mut bytes = ByteVec::new();
+    bytes.write_all_vectored(&parts).unwrap();
+    bytes.write_u8(5).unwrap();
+    assert_eq!(bytes.as_slice(), [2, 3, 4, 5]);
+}
+
+#[test]
+fn test_error_display_and_eq() {
+    let error = ReadError::InvalidTag {
+        ty: "Shape",
+        tag: 9,
+    };
+    assert_eq!(error.to_string(), "invalid tag 9 for Shape");
+    assert_eq!(format!("{:?}", error), error.to_string());
+    assert_eq!(error.clone(), error);
+    assert_ne!(
+        error,
+        ReadError::InvalidTag {
+            ty: "Shape",
+            tag: 8
+        }
+    );
+    assert_ne!(ReadError::Overflow, ReadError::OverlongEncoding);
+    assert_eq!(
+        ReadError::InsufficientData.in_field("id").at_offset(2),
+        ReadError::InsufficientData.in_field("id").at_offset(2)
+    );
+    assert_eq!(
+        WriteError::InsufficientSpace.to_string(),
+        "there is not enough space to complete the requested write"
+    );
+    assert_ne!(WriteError::InsufficientSpace, WriteError::Overflow);
+}
+
+#[test]
+fn test_custom_errors() {
+    let error = ReadError::custom("checksum mismatch");
+    assert_eq!(error.to_string(), "checksum mismatch");
+    assert_eq!(error, ReadError::custom("checksum mismatch"));
+    assert!(error.source().is_none());
+
+    let error = ReadError::other(ReadError::Overflow);
+    assert_eq!(
+        error.to_string(),
+        "decoded value does not fit in the target type"
+    );
+    assert_eq!(error.clone(), error);
+    assert_ne!(error, ReadError::other(ReadError::Overflow));
+    let error = error.in_field("len");
+    let source = error.source().unwrap();
+    assert_eq!(
+        source.downcast_ref::<ReadError>(),
+        Some(&ReadError::Overflow)
+    );
+
+    let error = WriteError::other("disk full");
+    assert_eq!(error.to_string(), "disk full");
+    assert!(error.source().is_some());
+    assert_eq!(WriteError::custom("nope"), WriteError::custom("nope"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_io_error_source() {
+    fn decode_boxed() -> Result<u8, Box<dyn std::error::Error>> {
+        let error = std::io::Error::other("unplugged");
+        Err(ReadError::from(error))?
+    }
+
+    let error = decode_boxed().unwrap_err();
+    let read_error = error.downcast_ref::<ReadError>().unwrap();
+    let source = read_error.source().unwrap();
+    assert_eq!(source.to_string(), "unplugged");
+    assert!(source.is::<std::io::Error>());
+    assert_eq!(read_error.clone(), *read_error);
+    assert_ne!(
+        ReadError::from(std::io::Error::other("unplugged")),
+        *read_error
+    );
+    let write_error = WriteError::from(std::io::Error::other("closed"));
+    assert_eq!(write_error.source().unwrap().to_string(), "closed");
+}
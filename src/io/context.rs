@@ -0,0 +1,197 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use core::fmt::{Display, Formatter};
+
+use crate::io::ReadError;
+
+/// One step on the way from the value being decoded down to the part of it that failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A named type, such as a derived struct or enum.
+    Type(&'static str),
+    /// A field of a struct or enum variant. Tuple fields are named by their index.
+    Field(&'static str),
+    /// The variant of an enum whose field failed.
+    Variant(&'static str),
+    /// An element of a sequence.
+    Index(usize),
+}
+
+/// Where a decode failed, attached to a [`ReadError`] as [`ReadError::Context`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorContext {
+    offset: Option<usize>,
+    /// Innermost first, since segments are added as the error propagates outwards.
+    path: Vec<PathSegment>,
+    error: ReadError,
+}
+
+impl ErrorContext {
+    /// How many bytes had been read when decoding failed, counted from wherever the outermost
+    /// [`decode`](crate::decode) started, or from the start of the buffer for
+    /// [`Cursor::decode`](crate::io::Cursor::decode).
+    #[inline]
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// The path to the failing value, outermost first.
+    #[inline]
+    pub fn path(&self) -> impl Iterator<Item = &PathSegment> {
+        self.path.iter().rev()
+    }
+
+    /// The error itself.
+    #[inline]
+    pub fn error(&self) -> &ReadError {
+        &self.error
+    }
+}
+
+/// Renders a path such as `Order.items[3].price`. Only the outermost type is named, since the
+/// field leading into a nested type already says where it is.
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut first = true;
+        for segment in self.path() {
+            match segment {
+                PathSegment::Type(name) if first => write!(f, "{}", name)?,
+                PathSegment::Type(_) => {}
+                PathSegment::Field(name) if first => write!(f, "{}", name)?,
+                PathSegment::Field(name) => write!(f, ".{}", name)?,
+                PathSegment::Variant(name) => write!(f, "::{}", name)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+impl ReadError {
+    /// The error without any [`ReadError::Context`] around it.
+    #[inline]
+    pub fn innermost(&self) -> &ReadError {
+        match self {
+            ReadError::Context(context) => &context.error,
+            error => error,
+        }
+    }
+
+    /// Like [`innermost`](ReadError::innermost), but by value.
+    #[inline]
+    pub fn into_innermost(self) -> ReadError {
+        match self {
+            ReadError::Context(context) => context.error,
+            error => error,
+        }
+    }
+
+    /// The context recorded for this error, if any.
+    #[inline]
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            ReadError::Context(context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Records that the error occurred `offset` bytes into the input, replacing any offset an
+    /// inner decode recorded.
+    #[inline]
+    pub fn at_offset(self, offset: usize) -> Self {
+        let mut context = self.into_context();
+        context.offset = Some(offset);
+        ReadError::Context(context)
+    }
+
+    /// Records that the error occurred within a value of type `name`.
+    #[inline]
+    pub fn in_type(self, name: &'static str) -> Self {
+        self.push(PathSegment::Type(name))
+    }
+
+    /// Records that the error occurred within the field `name`.
+    #[inline]
+    pub fn in_field(self, name: &'static str) -> Self {
+        self.push(PathSegment::Field(name))
+    }
+
+    /// Records that the error occurred within the enum variant `name`.
+    #[inline]
+    pub fn in_variant(self, name: &'static str) -> Self {
+        self.push(PathSegment::Variant(name))
+    }
+
+    /// Records that the error occurred within the element at `index` of a sequence.
+    #[inline]
+    pub fn at_index(self, index: usize) -> Self {
+        self.push(PathSegment::Index(index))
+    }
+
+    fn push(self, segment: PathSegment) -> Self {
+        let mut context = self.into_context();
+        context.path.push(segment);
+        ReadError::Context(context)
+    }
+
+    fn into_context(self) -> Box<ErrorContext> {
+        match self {
+            ReadError::Context(context) => context,
+            error => Box::new(ErrorContext {
+                offset: None,
+                path: Vec::new(),
+                error,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+use alloc::format;
+
+#[test]
+fn test_error_context() {
+    let error = ReadError::OverlongEncoding
+        .in_field("price")
+        .in_type("Item")
+        .at_index(3)
+        .in_field("items")
+        .in_type("Order")
+        .at_offset(412);
+    assert_eq!(
+        format!("{:?}", error),
+        "at offset 412 in Order.items[3].price: varint is padded with redundant zero bytes"
+    );
+    let context = error.context().unwrap();
+    assert_eq!(context.offset(), Some(412));
+    assert_eq!(context.path().count(), 5);
+    assert!(matches!(error.innermost(), ReadError::OverlongEncoding));
+    assert!(matches!(
+        error.into_innermost(),
+        ReadError::OverlongEncoding
+    ));
+}
+
+#[test]
+fn test_error_context_partial() {
+    let error = ReadError::InvalidBool(2)
+        .in_field("0")
+        .in_variant("Some")
+        .in_type("Maybe")
+        .at_index(1);
+    assert_eq!(
+        format!("{:?}", error),
+        "in [1]::Some.0: invalid bool byte: 0x02"
+    );
+    let error = ReadError::InsufficientData.at_offset(3).at_offset(7);
+    assert_eq!(
+        format!("{:?}", error),
+        "at offset 7: there is not enough data to complete the requested read"
+    );
+    assert!(ReadError::Overflow.context().is_none());
+    assert!(matches!(
+        ReadError::Overflow.innermost(),
+        ReadError::Overflow
+    ));
+}
@@ -0,0 +1,285 @@
+use alloc::vec::Vec;
+
+use crate::{
+    bytes::{ByteSlice, Bytes},
+    io::*,
+    Decode,
+};
+
+/// Reads from and writes over any [`Bytes`] buffer while tracking the current position.
+///
+/// Reading consumes bytes from the position onwards; writing overwrites them and fails with
+/// [`WriteError::InsufficientSpace`] rather than growing the buffer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cursor<B> {
+    inner: B,
+    position: usize,
+}
+
+impl<B: Bytes> Cursor<B> {
+    #[inline]
+    pub const fn new(inner: B) -> Self {
+        Cursor { inner, position: 0 }
+    }
+
+    /// The number of bytes read or written so far, or wherever [`set_position`] last moved it.
+    ///
+    /// [`set_position`]: Cursor::set_position
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves to `position`, which may be past the end, in which case reads and writes fail.
+    #[inline]
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    /// The number of bytes between the position and the end of the buffer.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.inner.len().saturating_sub(self.position)
+    }
+
+    /// The bytes between the position and the end of the buffer.
+    #[inline]
+    pub fn remaining_bytes(&self) -> &ByteSlice {
+        ByteSlice::from_slice(
+            self.inner
+                .as_slice()
+                .get(self.position..)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns the next `N` bytes without consuming them.
+    #[inline]
+    pub fn peek<const N: usize>(&self) -> Result<[u8; N], ReadError> {
+        let rest = self.inner.as_slice().get(self.position..);
+        match rest.and_then(<[u8]>::first_chunk) {
+            Some(bytes) => Ok(*bytes),
+            None => Err(ReadError::InsufficientData),
+        }
+    }
+
+    /// Moves past the next `n` bytes without reading them.
+    #[inline]
+    pub fn skip(&mut self, n: usize) -> Result<(), ReadError> {
+        self.take(n).map(|_| ())
+    }
+
+    /// Decodes a `T` from the position onwards, like [`decode`](crate::decode), but with the
+    /// offset in any [`ReadError::Context`] set to the position the cursor had reached when it
+    /// failed.
+    #[inline]
+    pub fn decode<T: Decode>(&mut self) -> Result<T, ReadError> {
+        crate::decode(self).map_err(|e| e.at_offset(self.position))
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Consumes the next `n` bytes. Fails even for `n == 0` if the position is past the end.
+    #[inline]
+    fn take(&mut self, n: usize) -> Result<&[u8], ReadError> {
+        let start = self.position;
+        let data = start
+            .checked_add(n)
+            .and_then(|end| self.inner.as_slice().get(start..end))
+            .ok_or(ReadError::InsufficientData)?;
+        self.position += n;
+        Ok(data)
+    }
+}
+
+impl<B: Bytes> Read for Cursor<B> {
+    #[inline]
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], ReadError> {
+        let bytes = self.peek()?;
+        self.position += N;
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn read_dynamic(&mut self, n: usize) -> Result<Vec<u8>, ReadError> {
+        self.take(n).map(<[u8]>::to_vec)
+    }
+
+    #[inline]
+    fn read_to_end(&mut self) -> Vec<u8> {
+        let data = self.remaining_bytes().as_slice().to_vec();
+        self.position += data.len();
+        data
+    }
+
+    #[inline]
+    fn read_exact_into(&mut self, buf: &mut ByteSlice) -> Result<(), ReadError> {
+        let data = self.take(buf.len())?;
+        buf.copy_from_slice(data);
+        Ok(())
+    }
+
+    #[inline]
+    fn skip(&mut self, n: usize) -> Result<(), ReadError> {
+        Cursor::skip(self, n)
+    }
+
+    #[inline]
+    fn remaining_hint(&self) -> Option<usize> {
+        Some(self.remaining())
+    }
+}
+
+impl<B: Bytes> Write for Cursor<B> {
+    #[inline]
+    fn write<'a>(&mut self, data: impl Into<&'a ByteSlice>) -> Result<(), WriteError> {
+        let data = data.into();
+        if self.position > self.inner.len() || data.len() > self.remaining() {
+            return Err(WriteError::InsufficientSpace);
+        }
+        let start = self.position;
+        self.inner
+            .range_mut(start..start + data.len())
+            .copy_from_slice(data);
+        self.position += data.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+use crate::{
+    bytes::{ByteArray, ByteVec},
+    impls::encode_bytes,
+    Encode,
+};
+
+#[cfg(test)]
+use alloc::{string::String, vec};
+
+#[test]
+fn test_cursor_read() {
+    let bytes = encode_bytes(&(1u16, String::from("abc"), 7u8));
+    let mut cursor = Cursor::new(ByteVec::from_vec(bytes));
+    assert_eq!(u16::decode(&mut cursor).unwrap(), 1);
+    assert_eq!(cursor.position(), 2);
+    assert_eq!(String::decode(&mut cursor).unwrap(), "abc");
+    assert_eq!(cursor.position(), 13);
+    assert_eq!(cursor.remaining(), 1);
+    assert_eq!(cursor.read_to_end(), [7]);
+    assert_eq!(cursor.remaining(), 0);
+    assert!(cursor.read::<1>().is_err());
+}
+
+#[test]
+fn test_cursor_peek_skip() {
+    let mut cursor = Cursor::new(ByteArray::from([1u8, 2, 3, 4]));
+    assert_eq!(cursor.peek::<2>().unwrap(), [1, 2]);
+    assert_eq!(cursor.position(), 0);
+    cursor.skip(3).unwrap();
+    assert_eq!(cursor.peek::<1>().unwrap(), [4]);
+    assert!(cursor.peek::<2>().is_err());
+    assert!(matches!(cursor.skip(2), Err(ReadError::InsufficientData)));
+    assert_eq!(cursor.position(), 3);
+    cursor.set_position(1);
+    assert_eq!(cursor.read::<2>().unwrap(), [2, 3]);
+    cursor.set_position(10);
+    assert_eq!(cursor.remaining(), 0);
+    assert!(cursor.remaining_bytes().is_empty());
+    assert!(cursor.read_dynamic(1).is_err());
+}
+
+#[test]
+fn test_cursor_read_helpers() {
+    let mut cursor = Cursor::new(ByteArray::from([1u8, 2, 3, 4, 5]));
+    assert_eq!(Read::remaining_hint(&cursor), Some(5));
+    assert_eq!(cursor.read_u8().unwrap(), 1);
+    let mut buf = [0u8; 2];
+    cursor
+        .read_exact_into(ByteSlice::from_slice_mut(&mut buf))
+        .unwrap();
+    assert_eq!(buf, [2, 3]);
+    Read::skip(&mut cursor, 1).unwrap();
+    assert_eq!(cursor.position(), 4);
+    assert!(cursor
+        .read_exact_into(ByteSlice::from_slice_mut(&mut buf))
+        .is_err());
+    assert_eq!(cursor.position(), 4);
+}
+
+#[test]
+fn test_cursor_write() {
+    let mut cursor = Cursor::new(ByteArray::<6>::new());
+    (1u16, 2u16).encode(&mut cursor).unwrap();
+    assert_eq!(cursor.position(), 4);
+    assert!(matches!(
+        3u32.encode(&mut cursor),
+        Err(WriteError::InsufficientSpace)
+    ));
+    cursor.set_position(0);
+    9u8.encode(&mut cursor).unwrap();
+    assert_eq!(*cursor.into_inner().as_slice(), [9, 0, 2, 0, 0, 0]);
+}
+
+#[test]
+fn test_cursor_past_end() {
+    let mut cursor = Cursor::new(ByteArray::<4>::new());
+    cursor.set_position(10);
+    assert_eq!(cursor.read_dynamic(0), Err(ReadError::InsufficientData));
+    assert_eq!(
+        cursor.read_exact_into(ByteSlice::from_slice_mut(&mut [])),
+        Err(ReadError::InsufficientData)
+    );
+    assert_eq!(cursor.read::<0>(), Err(ReadError::InsufficientData));
+    assert_eq!(cursor.skip(0), Err(ReadError::InsufficientData));
+    assert_eq!(cursor.write(&[]), Err(WriteError::InsufficientSpace));
+    assert_eq!(
+        [0u8; 0].encode(&mut cursor),
+        Err(WriteError::InsufficientSpace)
+    );
+    assert_eq!(
+        ByteArray::<0>::new().encode(&mut cursor),
+        Err(WriteError::InsufficientSpace)
+    );
+    assert_eq!(cursor.position(), 10);
+    assert!(cursor.read_to_end().is_empty());
+    cursor.set_position(usize::MAX);
+    assert_eq!(cursor.read_dynamic(1), Err(ReadError::InsufficientData));
+
+    cursor.set_position(4);
+    assert_eq!(cursor.read_dynamic(0).unwrap(), []);
+    cursor.write(&[]).unwrap();
+}
+
+#[test]
+fn test_cursor_sub_message_size() {
+    let mut cursor = Cursor::new(ByteVec::from_vec(vec![0; 32]));
+    let start = cursor.position();
+    (String::from("sub"), 5u64).encode(&mut cursor).unwrap();
+    assert_eq!(cursor.position() - start, 19);
+}
+
+#[test]
+fn test_cursor_error_position() {
+    let mut bytes = encode_bytes(&(5u32, true));
+    bytes.push(2);
+    let mut cursor = Cursor::new(ByteVec::from_vec(bytes));
+    assert_eq!(cursor.decode::<(u32, bool)>().unwrap(), (5, true));
+    let error = cursor.decode::<bool>().unwrap_err();
+    assert_eq!(error.context().unwrap().offset(), Some(6));
+    assert!(matches!(error.innermost(), ReadError::InvalidBool(2)));
+    let error = cursor.decode::<u8>().unwrap_err();
+    assert_eq!(error.context().unwrap().offset(), Some(6));
+}
@@ -0,0 +1,385 @@
+use alloc::vec::Vec;
+
+use crate::{bytes::ByteSlice, config::Config, io::*};
+
+/// Bounds on how much a single decode may read and allocate, so that a hostile length prefix
+/// cannot make a small message allocate gigabytes.
+///
+/// [`Limits::UNLIMITED`] sets every field to `usize::MAX`, meaning no limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// The most bytes that may be read in total.
+    pub max_bytes: usize,
+    /// The most bytes a single collection or buffer may occupy once decoded, counting each
+    /// element as at least one byte.
+    pub max_alloc: usize,
+    /// The most elements a single collection, string or buffer may have.
+    pub max_len: usize,
+    /// The most elements a single collection of zero-sized values, such as `Vec<()>`, may have.
+    /// Decoding those reads no input, so nothing else stops a length prefix from making a decode
+    /// loop over them for practically ever.
+    pub max_zero_sized_len: usize,
+    /// How deeply values may nest inside one another. See [`Read::enter`].
+    pub max_depth: usize,
+}
+
+impl Limits {
+    pub const UNLIMITED: Limits = Limits {
+        max_bytes: usize::MAX,
+        max_alloc: usize::MAX,
+        max_len: usize::MAX,
+        max_zero_sized_len: usize::MAX,
+        max_depth: usize::MAX,
+    };
+
+    /// The limits applied by [`decode`](crate::decode): only nesting depth and the length of
+    /// collections of zero-sized values are bounded. Everything else is bounded by the input.
+    pub const DEFAULT: Limits = Limits::UNLIMITED
+        .with_max_depth(128)
+        .with_max_zero_sized_len(1 << 24);
+
+    #[inline]
+    pub const fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    #[inline]
+    pub const fn with_max_alloc(mut self, max_alloc: usize) -> Self {
+        self.max_alloc = max_alloc;
+        self
+    }
+
+    #[inline]
+    pub const fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    #[inline]
+    pub const fn with_max_zero_sized_len(mut self, max_zero_sized_len: usize) -> Self {
+        self.max_zero_sized_len = max_zero_sized_len;
+        self
+    }
+
+    #[inline]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+impl Default for Limits {
+    #[inline]
+    fn default() -> Self {
+        Limits::DEFAULT
+    }
+}
+
+/// Wraps a reader and fails with [`ReadError::LimitExceeded`] once a decode goes past its
+/// [`Limits`].
+pub struct Limited<'a, R: ?Sized> {
+    io: &'a mut R,
+    limits: Limits,
+    bytes_read: usize,
+    depth: usize,
+}
+
+impl<'a, R: ?Sized> Limited<'a, R> {
+    #[inline]
+    pub fn new(io: &'a mut R, limits: Limits) -> Self {
+        Limited {
+            io,
+            limits,
+            bytes_read: 0,
+            depth: 0,
+        }
+    }
+
+    /// The number of bytes read through this wrapper so far.
+    #[inline]
+    pub fn bytes_read(&self) -> usize {
+        self.bytes_read
+    }
+
+    /// Reads `n` bytes with `read` if that stays within [`Limits::max_bytes`], counting them
+    /// only once the read succeeds.
+    #[inline]
+    fn consume<T>(
+        &mut self,
+        n: usize,
+        read: impl FnOnce(&mut R) -> Result<T, ReadError>,
+    ) -> Result<T, ReadError> {
+        match self.bytes_read.checked_add(n) {
+            Some(total) if total <= self.limits.max_bytes => {
+                let data = read(self.io)?;
+                self.bytes_read = total;
+                Ok(data)
+            }
+            _ => Err(ReadError::LimitExceeded),
+        }
+    }
+}
+
+impl<R: Read + ?Sized> Read for Limited<'_, R> {
+    #[inline]
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], ReadError> {
+        self.consume(N, R::read)
+    }
+
+    #[inline]
+    fn read_dynamic(&mut self, n: usize) -> Result<Vec<u8>, ReadError> {
+        if n > self.limits.max_alloc {
+            return Err(ReadError::LimitExceeded);
+        }
+        self.consume(n, |io| io.read_dynamic(n))
+    }
+
+    /// Reads everything that is left. Not limited, since the caller has asked for all of it.
+    #[inline]
+    fn read_to_end(&mut self) -> Vec<u8> {
+        let data = self.io.read_to_end();
+        self.bytes_read = self.bytes_read.saturating_add(data.len());
+        data
+    }
+
+    #[inline]
+    fn read_exact_into(&mut self, buf: &mut ByteSlice) -> Result<(), ReadError> {
+        self.consume(buf.len(), |io| io.read_exact_into(buf))
+    }
+
+    #[inline]
+    fn skip(&mut self, n: usize) -> Result<(), ReadError> {
+        self.consume(n, |io| io.skip(n))
+    }
+
+    #[inline]
+    fn remaining_hint(&self) -> Option<usize> {
+        self.io.remaining_hint()
+    }
+
+    #[inline]
+    fn config(&self) -> Config {
+        self.io.config()
+    }
+
+    #[inline]
+    fn check_len(&mut self, len: usize, elem_size: usize) -> Result<(), ReadError> {
+        if len > self.limits.max_len
+            || len.saturating_mul(elem_size.max(1)) > self.limits.max_alloc
+            || (elem_size == 0 && len > self.limits.max_zero_sized_len)
+        {
+            return Err(ReadError::LimitExceeded);
+        }
+        self.io.check_len(len, elem_size)
+    }
+
+    #[inline]
+    fn enter(&mut self) -> Result<(), ReadError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(ReadError::DepthLimitExceeded);
+        }
+        self.depth += 1;
+        self.io.enter()
+    }
+
+    #[inline]
+    fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        self.io.leave()
+    }
+}
+
+impl<'de, R: BorrowRead<'de> + ?Sized> BorrowRead<'de> for Limited<'_, R> {
+    #[inline]
+    fn read_borrowed(&mut self, n: usize) -> Result<&'de [u8], ReadError> {
+        self.consume(n, |io| io.read_borrowed(n))
+    }
+}
+
+#[cfg(test)]
+use crate::{decode_with_limits, Decode, Encode};
+
+#[cfg(test)]
+use crate::impls::encode_bytes;
+
+#[cfg(test)]
+use alloc::{string::String, vec};
+
+#[test]
+fn test_max_len() {
+    let bytes = encode_bytes(&vec![1u32, 2, 3]);
+    let limits = Limits::UNLIMITED.with_max_len(2);
+    assert!(matches!(
+        decode_with_limits::<Vec<u32>>(&mut &bytes[..], limits).map_err(ReadError::into_innermost),
+        Err(ReadError::LimitExceeded)
+    ));
+    let limits = Limits::UNLIMITED.with_max_len(3);
+    assert_eq!(
+        decode_with_limits::<Vec<u32>>(&mut &bytes[..], limits).unwrap(),
+        [1, 2, 3]
+    );
+}
+
+#[test]
+fn test_max_alloc() {
+    let bytes = encode_bytes(&vec![1u64, 2]);
+    let limits = Limits::UNLIMITED.with_max_alloc(15);
+    assert!(matches!(
+        decode_with_limits::<Vec<u64>>(&mut &bytes[..], limits).map_err(ReadError::into_innermost),
+        Err(ReadError::LimitExceeded)
+    ));
+    let bytes = encode_bytes(&String::from("hello"));
+    let limits = Limits::UNLIMITED.with_max_alloc(4);
+    assert!(matches!(
+        decode_with_limits::<String>(&mut &bytes[..], limits).map_err(ReadError::into_innermost),
+        Err(ReadError::LimitExceeded)
+    ));
+}
+
+#[test]
+fn test_max_bytes() {
+    let bytes = encode_bytes(&(1u64, 2u64));
+    let limits = Limits::UNLIMITED.with_max_bytes(12);
+    assert!(matches!(
+        decode_with_limits::<(u64, u64)>(&mut &bytes[..], limits)
+            .map_err(ReadError::into_innermost),
+        Err(ReadError::LimitExceeded)
+    ));
+    let mut slice = &bytes[..];
+    let mut limited = Limited::new(&mut slice, Limits::UNLIMITED.with_max_bytes(16));
+    assert_eq!(<(u64, u64)>::decode(&mut limited).unwrap(), (1, 2));
+    assert_eq!(limited.bytes_read(), 16);
+}
+
+#[test]
+fn test_hostile_length_prefix() {
+    let bytes = [0xFFu8, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0, 1];
+    assert!(matches!(
+        Vec::<u64>::decode(&mut &bytes[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::InsufficientData)
+    ));
+    let limits = Limits::UNLIMITED.with_max_alloc(1 << 20);
+    assert!(matches!(
+        decode_with_limits::<Vec<Vec<u8>>>(&mut &bytes[..], limits)
+            .map_err(ReadError::into_innermost),
+        Err(ReadError::LimitExceeded)
+    ));
+}
+
+#[test]
+fn test_zero_sized_elements() {
+    let bytes = u64::MAX.to_le_bytes();
+    assert_eq!(
+        crate::decode::<Vec<()>>(&mut &bytes[..])
+            .unwrap_err()
+            .innermost(),
+        &ReadError::LimitExceeded
+    );
+    let bytes = encode_bytes(&vec![(); 10]);
+    assert_eq!(crate::decode::<Vec<()>>(&mut &bytes[..]).unwrap().len(), 10);
+    let limits = Limits::UNLIMITED.with_max_alloc(9);
+    assert_eq!(
+        decode_with_limits::<Vec<()>>(&mut &bytes[..], limits)
+            .unwrap_err()
+            .innermost(),
+        &ReadError::LimitExceeded
+    );
+    let limits = Limits::UNLIMITED.with_max_zero_sized_len(9);
+    assert_eq!(
+        decode_with_limits::<Vec<()>>(&mut &bytes[..], limits)
+            .unwrap_err()
+            .innermost(),
+        &ReadError::LimitExceeded
+    );
+    let limits = Limits::UNLIMITED.with_max_zero_sized_len(10);
+    assert!(decode_with_limits::<Vec<()>>(&mut &bytes[..], limits).is_ok());
+}
+
+#[test]
+fn test_default_allows_large_buffers() {
+    let blob = vec![7u8; 17 << 20];
+    let bytes = encode_bytes(&blob);
+    assert_eq!(crate::decode::<Vec<u8>>(&mut &bytes[..]).unwrap(), blob);
+    let text = "x".repeat(17 << 20);
+    let bytes = encode_bytes(&text);
+    assert_eq!(crate::decode::<String>(&mut &bytes[..]).unwrap(), text);
+    assert_eq!(
+        crate::decode_borrowed::<&[u8]>(&mut &bytes[..])
+            .unwrap()
+            .len(),
+        17 << 20
+    );
+}
+
+#[test]
+fn test_limits_compose_with_config() {
+    use crate::config::{Config, Configured, ConstConfig, LengthPrefix};
+
+    struct ShortLengths;
+
+    impl ConstConfig for ShortLengths {
+        const CONFIG: Config = Config::DEFAULT.with_length_prefix(LengthPrefix::U8);
+    }
+
+    let bytes = [3u8, 1, 2, 3];
+    let mut slice = &bytes[..];
+    let mut configured = Configured::<_, ShortLengths>::new(&mut slice);
+    let mut limited = Limited::new(&mut configured, Limits::UNLIMITED.with_max_len(2));
+    assert!(matches!(
+        Vec::<u8>::decode(&mut limited),
+        Err(ReadError::LimitExceeded)
+    ));
+}
+
+/// A hand-written recursive list, nesting through `Option<Box<_>>`.
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+struct List(Option<alloc::boxed::Box<List>>);
+
+#[cfg(test)]
+impl Encode for List {
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        self.0.encode(io)
+    }
+}
+
+#[cfg(test)]
+impl Decode for List {
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        Option::decode(io).map(List)
+    }
+}
+
+#[test]
+fn test_max_depth() {
+    let bytes = vec![1u8; 1_000];
+    assert!(matches!(
+        crate::decode::<List>(&mut &bytes[..]).map_err(ReadError::into_innermost),
+        Err(ReadError::DepthLimitExceeded)
+    ));
+    let mut bytes = vec![1u8; 100];
+    bytes.push(0);
+    assert!(crate::decode::<List>(&mut &bytes[..]).is_ok());
+    let limits = Limits::UNLIMITED.with_max_depth(99);
+    assert!(matches!(
+        decode_with_limits::<List>(&mut &bytes[..], limits).map_err(ReadError::into_innermost),
+        Err(ReadError::DepthLimitExceeded)
+    ));
+}
+
+#[test]
+fn test_limited_skip_and_read_into() {
+    let bytes = [1u8, 2, 3, 4, 5];
+    let mut slice = &bytes[..];
+    let mut limited = Limited::new(&mut slice, Limits::UNLIMITED.with_max_bytes(4));
+    limited.skip(2).unwrap();
+    let mut buf = [0u8; 2];
+    limited
+        .read_exact_into(ByteSlice::from_slice_mut(&mut buf))
+        .unwrap();
+    assert_eq!(buf, [3, 4]);
+    assert_eq!(limited.bytes_read(), 4);
+    assert_eq!(limited.remaining_hint(), Some(1));
+    assert!(matches!(limited.skip(1), Err(ReadError::LimitExceeded)));
+}
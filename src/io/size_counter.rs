@@ -0,0 +1,47 @@
+use crate::{bytes::ByteSlice, io::*};
+
+/// A writer that discards everything written to it and counts the bytes instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizeCounter {
+    len: usize,
+}
+
+impl SizeCounter {
+    #[inline]
+    pub const fn new() -> Self {
+        SizeCounter { len: 0 }
+    }
+
+    /// The number of bytes written so far.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Write for SizeCounter {
+    #[inline]
+    fn write<'a>(&mut self, data: impl Into<&'a ByteSlice>) -> Result<(), WriteError> {
+        self.len = self.len.saturating_add(data.into().len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+use crate::Encode;
+
+#[test]
+fn test_size_counter() {
+    let mut counter = SizeCounter::new();
+    assert!(counter.is_empty());
+    counter.write(&[1, 2, 3]).unwrap();
+    counter.write(&[]).unwrap();
+    assert_eq!(counter.len(), 3);
+    (1u16, true).encode(&mut counter).unwrap();
+    assert_eq!(counter.len(), 6);
+}
@@ -0,0 +1,340 @@
+use std::io::{ErrorKind, IoSlice};
+
+use crate::{bytes::ByteSlice, io::*};
+
+/// How many bytes [`IoReader::read_dynamic`] reads at a time, so that a large length prefix only
+/// costs memory once the data actually arrives.
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Adapts a [`std::io::Read`] such as a `File`, `TcpStream` or `BufReader` into a [`Read`].
+///
+/// Running out of input fails with [`ReadError::InsufficientData`] and every other failure with
+/// [`ReadError::IoError`]. Reads go straight to the inner reader, so wrap unbuffered sources in a
+/// `BufReader`.
+#[derive(Debug, Default)]
+pub struct IoReader<R> {
+    inner: R,
+}
+
+impl<R> IoReader<R> {
+    #[inline]
+    pub const fn new(inner: R) -> Self {
+        IoReader { inner }
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[inline]
+fn read_error(error: std::io::Error) -> ReadError {
+    match error.kind() {
+        ErrorKind::UnexpectedEof => ReadError::InsufficientData,
+        _ => ReadError::from(error),
+    }
+}
+
+impl<R: std::io::Read> Read for IoReader<R> {
+    #[inline]
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], ReadError> {
+        let mut buf = [0u8; N];
+        self.inner.read_exact(&mut buf).map_err(read_error)?;
+        Ok(buf)
+    }
+
+    fn read_dynamic(&mut self, n: usize) -> Result<Vec<u8>, ReadError> {
+        let mut data = Vec::with_capacity(n.min(READ_CHUNK));
+        while data.len() < n {
+            let start = data.len();
+            data.resize(start + (n - start).min(READ_CHUNK), 0);
+            self.inner
+                .read_exact(&mut data[start..])
+                .map_err(read_error)?;
+        }
+        Ok(data)
+    }
+
+    /// Reads until the inner reader is exhausted. An error ends the read early, returning
+    /// whatever was read before it.
+    fn read_to_end(&mut self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let _ = self.inner.read_to_end(&mut data);
+        data
+    }
+
+    #[inline]
+    fn read_exact_into(&mut self, buf: &mut ByteSlice) -> Result<(), ReadError> {
+        self.inner.read_exact(buf).map_err(read_error)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), ReadError> {
+        let n = n as u64;
+        let skipped = std::io::copy(
+            &mut std::io::Read::take(&mut self.inner, n),
+            &mut std::io::sink(),
+        )
+        .map_err(read_error)?;
+        match skipped == n {
+            true => Ok(()),
+            false => Err(ReadError::InsufficientData),
+        }
+    }
+}
+
+/// Adapts a [`std::io::Write`] such as a `File`, `TcpStream` or `BufWriter` into a [`Write`].
+///
+/// A writer that stops accepting bytes fails with [`WriteError::InsufficientSpace`] and every
+/// other failure with [`WriteError::IoError`]. Writes go straight to the inner writer, so wrap
+/// unbuffered sinks in a `BufWriter`.
+#[derive(Debug, Default)]
+pub struct IoWriter<W> {
+    inner: W,
+}
+
+impl<W> IoWriter<W> {
+    #[inline]
+    pub const fn new(inner: W) -> Self {
+        IoWriter { inner }
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[inline]
+fn write_error(error: std::io::Error) -> WriteError {
+    match error.kind() {
+        ErrorKind::WriteZero => WriteError::InsufficientSpace,
+        _ => WriteError::from(error),
+    }
+}
+
+impl<W: std::io::Write> Write for IoWriter<W> {
+    #[inline]
+    fn write<'a>(&mut self, data: impl Into<&'a ByteSlice>) -> Result<(), WriteError> {
+        self.inner.write_all(data.into()).map_err(write_error)
+    }
+
+    /// Hands all of `bufs` to [`std::io::Write::write_vectored`] at once, so that sinks which
+    /// support it can gather them into a single system call.
+    fn write_all_vectored(&mut self, bufs: &[&ByteSlice]) -> Result<(), WriteError> {
+        // Empty buffers are left out, since a sink handed only those would report writing
+        // nothing, which would be mistaken for running out of space.
+        let mut slices: Vec<IoSlice<'_>> = bufs
+            .iter()
+            .filter(|buf| !buf.is_empty())
+            .map(|buf| IoSlice::new(buf))
+            .collect();
+        let mut slices = &mut slices[..];
+        while !slices.is_empty() {
+            match self.inner.write_vectored(slices) {
+                Ok(0) => return Err(WriteError::InsufficientSpace),
+                Ok(n) => IoSlice::advance_slices(&mut slices, n),
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(write_error(error)),
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), WriteError> {
+        self.inner.flush().map_err(write_error)
+    }
+}
+
+#[cfg(test)]
+use crate::{decode, Decode, Encode};
+
+#[cfg(test)]
+use crate::impls::encode_bytes;
+
+/// Hands out at most one byte per call, like a slow socket.
+#[cfg(test)]
+struct Trickle<'a>(&'a [u8]);
+
+#[cfg(test)]
+impl std::io::Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match (self.0.split_first(), buf.first_mut()) {
+            (Some((byte, rest)), Some(slot)) => {
+                *slot = *byte;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+/// Fails every read and write.
+#[cfg(test)]
+struct Broken;
+
+#[cfg(test)]
+impl std::io::Read for Broken {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("broken"))
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for Broken {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("broken"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_io_reader() {
+    let value = (7u32, String::from("streamed"), vec![1u64, 2, 3]);
+    let bytes = encode_bytes(&value);
+    let mut reader = IoReader::new(Trickle(&bytes));
+    assert_eq!(
+        decode::<(u32, String, Vec<u64>)>(&mut reader).unwrap(),
+        value
+    );
+    assert!(reader.read_to_end().is_empty());
+
+    let mut reader = IoReader::new(std::io::BufReader::new(&bytes[..]));
+    assert_eq!(u32::decode(&mut reader).unwrap(), 7);
+    assert_eq!(reader.read_to_end().len(), bytes.len() - 4);
+}
+
+#[test]
+fn test_io_reader_eof() {
+    let mut reader = IoReader::new(Trickle(&[1, 2]));
+    assert!(matches!(
+        u32::decode(&mut reader),
+        Err(ReadError::InsufficientData)
+    ));
+    let mut bytes = 20_000u64.to_le_bytes().to_vec();
+    bytes.resize(8 + 10_000, 9);
+    assert!(matches!(
+        Vec::<u8>::decode(&mut IoReader::new(&bytes[..])),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_io_reader_chunks() {
+    let mut bytes = 20_000u64.to_le_bytes().to_vec();
+    bytes.resize(8 + 20_000, 9);
+    let decoded = Vec::<u8>::decode(&mut IoReader::new(Trickle(&bytes))).unwrap();
+    assert_eq!(decoded, bytes[8..]);
+}
+
+#[test]
+fn test_io_reader_hostile_length() {
+    let bytes = [0xFFu8, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0];
+    assert!(matches!(
+        Vec::<u8>::decode(&mut IoReader::new(&bytes[..])),
+        Err(ReadError::InsufficientData)
+    ));
+}
+
+#[test]
+fn test_io_reader_error() {
+    assert!(matches!(
+        u8::decode(&mut IoReader::new(Broken)),
+        Err(ReadError::IoError(_))
+    ));
+}
+
+#[test]
+fn test_io_reader_helpers() {
+    let bytes: Vec<u8> = (0..20).collect();
+    let mut reader = IoReader::new(Trickle(&bytes));
+    assert_eq!(reader.remaining_hint(), None);
+    let mut buf = [0u8; 4];
+    reader
+        .read_exact_into(ByteSlice::from_slice_mut(&mut buf))
+        .unwrap();
+    assert_eq!(buf, [0, 1, 2, 3]);
+    reader.skip(10).unwrap();
+    assert_eq!(reader.read_u8().unwrap(), 14);
+    assert!(matches!(reader.skip(6), Err(ReadError::InsufficientData)));
+    assert!(matches!(
+        IoReader::new(Broken).skip(1),
+        Err(ReadError::IoError(_))
+    ));
+}
+
+#[test]
+fn test_io_writer_helpers() {
+    let parts = [
+        ByteSlice::from_slice(&[]),
+        ByteSlice::from_slice(&[1, 2]),
+        ByteSlice::from_slice(&[3]),
+    ];
+    let mut writer = IoWriter::new(std::io::BufWriter::new(Vec::new()));
+    writer.write_all_vectored(&parts).unwrap();
+    writer.write_u8(4).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.get_ref().get_ref(), &[1, 2, 3, 4]);
+
+    let mut buf = [0u8; 2];
+    let mut writer = IoWriter::new(&mut buf[..]);
+    assert!(matches!(
+        writer.write_all_vectored(&parts),
+        Err(WriteError::InsufficientSpace)
+    ));
+
+    let empty = ByteSlice::from_slice(&[]);
+    let mut full = [0u8; 0];
+    let mut writer = IoWriter::new(&mut full[..]);
+    writer.write_all_vectored(&[empty, empty]).unwrap();
+    let mut buf = [0u8; 3];
+    let mut writer = IoWriter::new(&mut buf[..]);
+    writer
+        .write_all_vectored(&[parts[1], empty, empty, parts[2]])
+        .unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+}
+
+#[test]
+fn test_io_writer() {
+    let value = (String::from("out"), [1u16, 2], Some(-1i64));
+    let mut writer = IoWriter::new(Vec::new());
+    value.encode(&mut writer).unwrap();
+    assert_eq!(writer.into_inner(), encode_bytes(&value));
+
+    let mut buf = [0u8; 4];
+    let mut writer = IoWriter::new(&mut buf[..]);
+    assert!(matches!(
+        5u64.encode(&mut writer),
+        Err(WriteError::InsufficientSpace)
+    ));
+    assert!(matches!(
+        1u8.encode(&mut IoWriter::new(Broken)),
+        Err(WriteError::IoError(_))
+    ));
+}
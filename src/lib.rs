@@ -1,15 +1,283 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
 pub mod bytes;
+pub mod config;
+pub mod framing;
+mod impls;
 pub mod io;
+pub mod varint;
+pub mod with;
 
+use alloc::vec::Vec;
+use bytes::{ByteArray, ByteSlice, ByteVec};
+use config::{Configured, ConstConfig};
 use io::*;
 
-pub trait Codec: Sized {
+#[cfg(feature = "derive")]
+pub use codec_derive::{BorrowDecode, Codec, Decode, Encode, MaxEncodedLen};
+
+/// A type that can be written to an [`io::Write`].
+///
+/// Unlike [`Decode`], this does not require `Sized`, so `str`, `[T]` and
+/// [`ByteSlice`](bytes::ByteSlice) implement it directly, and references encode exactly like the
+/// value they point to.
+pub trait Encode {
     fn encode(&self, io: &mut impl Write) -> Result<(), WriteError>;
+
+    /// The exact number of bytes [`encode`](Encode::encode) writes for this value under
+    /// [`Config::DEFAULT`](config::Config::DEFAULT).
+    ///
+    /// By default this encodes into a [`SizeCounter`]; impls that can compute it directly
+    /// override it. If encoding fails, the bytes written before the failure are counted.
+    #[inline]
+    fn encoded_size(&self) -> usize {
+        let mut counter = SizeCounter::new();
+        let _ = self.encode(&mut counter);
+        counter.len()
+    }
+
+    /// Encodes a contiguous run of values. Overridden by types with a bulk byte representation.
+    #[doc(hidden)]
+    #[inline]
+    fn encode_slice(slice: &[Self], io: &mut impl Write) -> Result<(), WriteError>
+    where
+        Self: Sized,
+    {
+        for item in slice {
+            item.encode(io)?;
+        }
+        Ok(())
+    }
+}
+
+/// A type that can be read back from an [`io::Read`] as an owned value.
+pub trait Decode: Sized {
     fn decode(io: &mut impl Read) -> Result<Self, ReadError>;
+
+    /// Decodes over an existing value, reusing the buffers it already owns. The result is the
+    /// same as assigning [`decode`](Decode::decode)'s, which is what the default does.
+    ///
+    /// `Vec`, `String`, `ByteVec`, the maps and sets and derived types override it so that
+    /// decoding many messages into one value stops allocating once its buffers are big enough.
+    /// If decoding fails, `self` is left valid but with unspecified contents.
+    #[inline]
+    fn decode_in_place(&mut self, io: &mut impl Read) -> Result<(), ReadError> {
+        *self = Self::decode(io)?;
+        Ok(())
+    }
+
+    /// Decodes a fixed-size array of values. Overridden by types with a bulk byte representation.
+    #[doc(hidden)]
+    #[inline]
+    fn decode_array<const N: usize>(io: &mut impl Read) -> Result<[Self; N], ReadError> {
+        impls::decode_array(io, Self::decode)
+    }
+
+    /// Decodes `len` values into a `Vec`. Overridden by types with a bulk byte representation.
+    #[doc(hidden)]
+    #[inline]
+    fn decode_vec(len: usize, io: &mut impl Read) -> Result<Vec<Self>, ReadError> {
+        impls::decode_vec(len, io, Self::decode)
+    }
+
+    /// Replaces the contents of `vec` with `len` decoded values, decoding in place over the
+    /// values it already holds. Overridden by types with a bulk byte representation.
+    #[doc(hidden)]
+    #[inline]
+    fn decode_vec_in_place(
+        vec: &mut Vec<Self>,
+        len: usize,
+        io: &mut impl Read,
+    ) -> Result<(), ReadError> {
+        vec.truncate(len);
+        io.enter()?;
+        for (index, item) in vec.iter_mut().enumerate() {
+            item.decode_in_place(io).map_err(|e| e.at_index(index))?;
+        }
+        vec.reserve(impls::initial_capacity::<Self>(len - vec.len()));
+        for index in vec.len()..len {
+            vec.push(Self::decode(io).map_err(|e| e.at_index(index))?);
+        }
+        io.leave();
+        Ok(())
+    }
+}
+
+/// Shorthand for types that are both [`Encode`] and [`Decode`], implemented for all of them.
+pub trait Codec: Encode + Decode {}
+
+impl<T: Encode + Decode> Codec for T {}
+
+/// A type that can be decoded from input living for `'de`, possibly borrowing from it.
+///
+/// `&'de [u8]`, `&'de ByteSlice` and `&'de str` point straight into the input rather than copying
+/// out of it, which makes large payloads free to decode from an in-memory buffer:
+///
+/// ```
+/// let bytes = [5, 0, 0, 0, 0, 0, 0, 0, b'h', b'e', b'l', b'l', b'o'];
+/// let text: &str = codec::decode_borrowed(&mut &bytes[..]).unwrap();
+/// assert_eq!(text, "hello");
+/// assert_eq!(text.as_ptr(), bytes[8..].as_ptr());
+/// ```
+///
+/// `Option`, `Result`, `Vec`, `Box`, tuples and arrays of borrowed values borrow too, as do types
+/// deriving `BorrowDecode`. Every other type in this crate and every type deriving [`Decode`]
+/// implements it by decoding an owned value. Types with a hand-written `Decode` impl can do the
+/// same by calling [`Decode::decode`] from `borrow_decode`.
+pub trait BorrowDecode<'de>: Sized {
+    fn borrow_decode(io: &mut impl BorrowRead<'de>) -> Result<Self, ReadError>;
+
+    /// Decodes a fixed-size array of values. Overridden by types with a bulk byte representation.
+    #[doc(hidden)]
+    #[inline]
+    fn borrow_decode_array<const N: usize>(
+        io: &mut impl BorrowRead<'de>,
+    ) -> Result<[Self; N], ReadError> {
+        impls::decode_array(io, Self::borrow_decode)
+    }
+
+    /// Decodes `len` values into a `Vec`. Overridden by types with a bulk byte representation.
+    #[doc(hidden)]
+    #[inline]
+    fn borrow_decode_vec(
+        len: usize,
+        io: &mut impl BorrowRead<'de>,
+    ) -> Result<Vec<Self>, ReadError> {
+        impls::decode_vec(len, io, Self::borrow_decode)
+    }
+}
+
+/// An [`Encode`] type whose encoding never exceeds a size known at compile time.
+///
+/// Like [`Encode::encoded_size`], the bound assumes [`Config::DEFAULT`](config::Config::DEFAULT).
+/// Types with unbounded encodings, such as `Vec` and `String`, do not implement it.
+pub trait MaxEncodedLen: Encode {
+    const MAX_ENCODED_LEN: usize;
+}
+
+/// Checks at compile time that `N` bytes can hold any encoded `T`.
+struct AssertFits<T, const N: usize>(core::marker::PhantomData<T>);
+
+impl<T: MaxEncodedLen, const N: usize> AssertFits<T, N> {
+    const OK: () = assert!(
+        N >= T::MAX_ENCODED_LEN,
+        "the ByteArray is smaller than the type's MAX_ENCODED_LEN"
+    );
+}
+
+/// Encodes `value` into a stack-allocated [`ByteArray`], returning it along with the number of
+/// bytes actually written.
+///
+/// `N` is typically `T::MAX_ENCODED_LEN`; an `N` too small to hold every possible `T` fails to
+/// compile.
+#[inline]
+pub fn encode_to_byte_array<T: MaxEncodedLen, const N: usize>(
+    value: &T,
+) -> Result<(ByteArray<N>, usize), WriteError> {
+    #[allow(clippy::let_unit_value)]
+    let () = AssertFits::<T, N>::OK;
+    let mut array = ByteArray::<N>::new();
+    let written = encode_to_slice(value, ByteSlice::from_slice_mut(&mut array[..]))?;
+    Ok((array, written))
+}
+
+/// Encodes `value` into a new [`ByteVec`].
+///
+/// Writing to a growable buffer cannot run out of space, so this only fails if `value`'s own
+/// `encode` does.
+#[inline]
+pub fn encode_to_vec<T: Encode + ?Sized>(value: &T) -> Result<ByteVec, WriteError> {
+    let mut vec = ByteVec::new();
+    value.encode(&mut vec)?;
+    Ok(vec)
+}
+
+/// Encodes `value` into the start of `slice`, returning the number of bytes written.
+#[inline]
+pub fn encode_to_slice<T: Encode + ?Sized>(
+    value: &T,
+    slice: &mut ByteSlice,
+) -> Result<usize, WriteError> {
+    let capacity = slice.len();
+    let mut rest = slice;
+    value.encode(&mut rest)?;
+    Ok(capacity - rest.len())
+}
+
+/// Decodes a `T` from `io` under [`Limits::DEFAULT`], which bounds nesting depth and the length
+/// of collections of zero-sized values so that adversarial input cannot overflow the stack
+/// through recursive types or spin on a huge length prefix.
+///
+/// Errors are wrapped in [`ReadError::Context`] with the number of bytes read before the failure
+/// and, for derived types, the path to the field that failed.
+///
+/// `Decode` impls decoding their own fields should call `T::decode` rather than this, which would
+/// wrap the reader again at every level.
+#[inline]
+pub fn decode<T: Decode>(io: &mut impl Read) -> Result<T, ReadError> {
+    decode_with_limits(io, Limits::DEFAULT)
+}
+
+/// Decodes a `T` that must take up the whole of `bytes`, like [`decode`] but failing with
+/// [`ReadError::TrailingBytes`] if anything is left over, so that a valid message with garbage
+/// appended is rejected.
+#[inline]
+pub fn decode_exact<T: Decode>(bytes: &[u8]) -> Result<T, ReadError> {
+    let mut rest = bytes;
+    let value = decode(&mut rest)?;
+    match rest.len() {
+        0 => Ok(value),
+        remaining => Err(ReadError::TrailingBytes { remaining }),
+    }
+}
+
+/// [`decode_exact`] for any [`Bytes`](bytes::Bytes) buffer, such as a [`ByteVec`] or
+/// [`ByteArray`].
+#[inline]
+pub fn from_bytes<T: Decode>(bytes: &(impl bytes::Bytes + ?Sized)) -> Result<T, ReadError> {
+    decode_exact(bytes.as_slice())
+}
+
+/// Decodes a `T` that may borrow from `io`, under [`Limits::DEFAULT`] like [`decode`].
+#[inline]
+pub fn decode_borrowed<'de, T: BorrowDecode<'de>>(
+    io: &mut impl BorrowRead<'de>,
+) -> Result<T, ReadError> {
+    let mut limited = Limited::new(io, Limits::DEFAULT);
+    T::borrow_decode(&mut limited).map_err(|e| e.at_offset(limited.bytes_read()))
+}
+
+/// Decodes a `T` from `io`, failing with [`ReadError::LimitExceeded`] if it reads or allocates
+/// more than `limits` allow.
+#[inline]
+pub fn decode_with_limits<T: Decode>(io: &mut impl Read, limits: Limits) -> Result<T, ReadError> {
+    let mut limited = Limited::new(io, limits);
+    T::decode(&mut limited).map_err(|e| e.at_offset(limited.bytes_read()))
+}
+
+/// Encodes `value` to `io` using the configuration carried by `C` instead of the writer's own.
+#[inline]
+pub fn encode_with<T: Encode + ?Sized, C: ConstConfig>(
+    value: &T,
+    io: &mut impl Write,
+    _config: C,
+) -> Result<(), WriteError> {
+    value.encode(&mut Configured::<_, C>::new(io))
+}
+
+/// Decodes a `T` from `io` using the configuration carried by `C` instead of the reader's own.
+#[inline]
+pub fn decode_with<T: Decode, C: ConstConfig>(
+    io: &mut impl Read,
+    _config: C,
+) -> Result<T, ReadError> {
+    decode(&mut Configured::<_, C>::new(io))
 }
 
-pub fn decode<T: Codec>(io: &mut impl Read) -> Result<T, ReadError> {
-    T::decode(io)
+/// Support code for `codec-derive`. Not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::varint::{decode_varint, encode_varint};
 }
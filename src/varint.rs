@@ -0,0 +1,213 @@
+//! Variable-length integers.
+//!
+//! Unsigned values are written as LEB128: seven bits per byte, least significant group first,
+//! with the high bit of each byte set when more bytes follow. Signed values are zigzag encoded
+//! first so that small negative numbers stay small. Values below 128 take a single byte and a
+//! `u64` never takes more than ten.
+
+use crate::{io::*, Decode, Encode};
+
+/// The most bytes a LEB128 encoded `u64` can take.
+pub const MAX_VARINT_LEN: usize = 10;
+
+/// Writes `value` as an unsigned LEB128 varint.
+#[inline]
+pub fn encode_varint(mut value: u64, io: &mut impl Write) -> Result<(), WriteError> {
+    let mut buf = [0u8; MAX_VARINT_LEN];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+            return io.write(&buf[..=len]);
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+}
+
+/// Reads an unsigned LEB128 varint written by [`encode_varint`].
+///
+/// Fails with [`ReadError::Overflow`] if the value does not fit in a `u64`, and with
+/// [`ReadError::OverlongEncoding`] if it was padded with redundant zero groups, so every value
+/// has exactly one accepted encoding.
+#[inline]
+pub fn decode_varint(io: &mut impl Read) -> Result<u64, ReadError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let [byte] = io.read()?;
+        let bits = (byte & 0x7F) as u64;
+        if shift == 63 && byte > 1 {
+            return Err(ReadError::Overflow);
+        }
+        if byte == 0 && shift != 0 {
+            return Err(ReadError::OverlongEncoding);
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    unreachable!("the final group either terminates or overflows")
+}
+
+/// Maps signed integers onto unsigned ones so that values near zero stay small:
+/// `0, -1, 1, -2, ...` become `0, 1, 2, 3, ...`.
+#[inline]
+pub const fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Inverse of [`zigzag_encode`].
+#[inline]
+pub const fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// An integer encoded as a varint rather than at its fixed width.
+///
+/// Decoding fails with [`ReadError::Overflow`] if the encoded value does not fit in `T`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarInt<T>(pub T);
+
+impl<T> From<T> for VarInt<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        VarInt(value)
+    }
+}
+
+macro_rules! impl_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl Encode for VarInt<$ty> {
+                #[inline]
+                fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+                    encode_varint(self.0 as u64, io)
+                }
+            }
+
+            impl Decode for VarInt<$ty> {
+                #[inline]
+                fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+                    <$ty>::try_from(decode_varint(io)?)
+                        .map(VarInt)
+                        .map_err(|_| ReadError::Overflow)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl Encode for VarInt<$ty> {
+                #[inline]
+                fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+                    encode_varint(zigzag_encode(self.0 as i64), io)
+                }
+            }
+
+            impl Decode for VarInt<$ty> {
+                #[inline]
+                fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+                    <$ty>::try_from(zigzag_decode(decode_varint(io)?))
+                        .map(VarInt)
+                        .map_err(|_| ReadError::Overflow)
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+impl_signed!(i8, i16, i32, i64, isize);
+
+#[cfg(test)]
+use crate::impls::roundtrip;
+
+#[cfg(test)]
+use alloc::vec;
+
+#[test]
+fn test_unsigned() {
+    assert_eq!(roundtrip(VarInt(0u32)), [0]);
+    assert_eq!(roundtrip(VarInt(127u32)), [0x7F]);
+    assert_eq!(roundtrip(VarInt(128u32)), [0x80, 0x01]);
+    assert_eq!(roundtrip(VarInt(300u16)), [0xAC, 0x02]);
+    let max = roundtrip(VarInt(u64::MAX));
+    assert_eq!(max.len(), MAX_VARINT_LEN);
+    assert_eq!(max[9], 0x01);
+    roundtrip(VarInt(usize::MAX));
+}
+
+#[test]
+fn test_signed() {
+    assert_eq!(roundtrip(VarInt(0i32)), [0]);
+    assert_eq!(roundtrip(VarInt(-1i32)), [1]);
+    assert_eq!(roundtrip(VarInt(1i32)), [2]);
+    assert_eq!(roundtrip(VarInt(-64i64)), [0x7F]);
+    assert_eq!(roundtrip(VarInt(64i64)), [0x80, 0x01]);
+    roundtrip(VarInt(i64::MIN));
+    roundtrip(VarInt(i64::MAX));
+    roundtrip(VarInt(i8::MIN));
+}
+
+#[test]
+fn test_zigzag() {
+    for value in [0i64, -1, 1, -2, 2, i64::MIN, i64::MAX] {
+        assert_eq!(zigzag_decode(zigzag_encode(value)), value);
+    }
+    assert_eq!(zigzag_encode(i64::MIN), u64::MAX);
+}
+
+#[test]
+fn test_narrowing_overflow() {
+    let bytes = roundtrip(VarInt(256u32));
+    assert!(matches!(
+        VarInt::<u8>::decode(&mut &bytes[..]),
+        Err(ReadError::Overflow)
+    ));
+    let bytes = roundtrip(VarInt(-129i32));
+    assert!(matches!(
+        VarInt::<i8>::decode(&mut &bytes[..]),
+        Err(ReadError::Overflow)
+    ));
+}
+
+#[test]
+fn test_u64_overflow() {
+    let mut bytes = vec![0xFF; 9];
+    bytes.push(0x02);
+    assert!(matches!(
+        decode_varint(&mut &bytes[..]),
+        Err(ReadError::Overflow)
+    ));
+    let too_long = [0x80u8; 11];
+    assert!(matches!(
+        decode_varint(&mut &too_long[..]),
+        Err(ReadError::Overflow)
+    ));
+}
+
+#[test]
+fn test_overlong() {
+    assert!(matches!(
+        decode_varint(&mut &[0x80u8, 0x00][..]),
+        Err(ReadError::OverlongEncoding)
+    ));
+    assert!(matches!(
+        decode_varint(&mut &[0xFFu8, 0x80, 0x00][..]),
+        Err(ReadError::OverlongEncoding)
+    ));
+}
+
+#[test]
+fn test_truncated() {
+    assert!(matches!(
+        decode_varint(&mut &[0x80u8][..]),
+        Err(ReadError::InsufficientData)
+    ));
+}
@@ -0,0 +1,248 @@
+//! Encoding foreign types through local strategies.
+//!
+//! Neither `Encode`, `Decode` nor a type like `uuid::Uuid` is local to a downstream crate, so
+//! the orphan rule forbids implementing one for the other there. Instead, define a local marker
+//! type, implement [`CodecWith`] on it for the foreign type, and wrap values in [`As`]:
+//!
+//! ```
+//! use core::time::Duration;
+//! use codec::{io::*, with::{As, CodecWith}};
+//!
+//! struct Millis;
+//!
+//! impl CodecWith<Duration> for Millis {
+//!     fn encode(value: &Duration, io: &mut impl Write) -> Result<(), WriteError> {
+//!         codec::Encode::encode(&(value.as_millis() as u64), io)
+//!     }
+//!
+//!     fn decode(io: &mut impl Read) -> Result<Duration, ReadError> {
+//!         <u64 as codec::Decode>::decode(io).map(Duration::from_millis)
+//!     }
+//! }
+//!
+//! let timeouts: Vec<As<Duration, Millis>> = vec![As::new(Duration::from_secs(1))];
+//! ```
+//!
+//! `As<T, S>` implements `Codec`, so it composes with every other impl (`Vec<As<T, S>>`,
+//! `Option<As<T, S>>`, maps, tuples, derived structs and so on). With the `derive` feature a
+//! strategy can also be named directly on a field with `#[codec(with = "Millis")]`.
+
+use core::{
+    fmt::{Debug, Formatter},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{io::*, Decode, Encode};
+
+/// A strategy for encoding values of type `T`, implemented on a local marker type.
+pub trait CodecWith<T> {
+    fn encode(value: &T, io: &mut impl Write) -> Result<(), WriteError>;
+    fn decode(io: &mut impl Read) -> Result<T, ReadError>;
+}
+
+/// A `T` that is encoded and decoded using the strategy `S`.
+///
+/// Encodes to exactly the bytes `S` writes, with no framing of its own.
+pub struct As<T, S> {
+    value: T,
+    _strategy: PhantomData<fn() -> S>,
+}
+
+impl<T, S> As<T, S> {
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        As {
+            value,
+            _strategy: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, S: CodecWith<T>> Encode for As<T, S> {
+    #[inline]
+    fn encode(&self, io: &mut impl Write) -> Result<(), WriteError> {
+        S::encode(&self.value, io)
+    }
+}
+
+impl<T, S: CodecWith<T>> Decode for As<T, S> {
+    #[inline]
+    fn decode(io: &mut impl Read) -> Result<Self, ReadError> {
+        S::decode(io).map(As::new)
+    }
+}
+
+impl<T, S> From<T> for As<T, S> {
+    #[inline]
+    fn from(value: T) -> Self {
+        As::new(value)
+    }
+}
+
+impl<T, S> Deref for As<T, S> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, S> DerefMut for As<T, S> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+// Implemented by hand so that the strategy marker doesn't need to implement these traits.
+
+impl<T: Clone, S> Clone for As<T, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        As::new(self.value.clone())
+    }
+}
+
+impl<T: Copy, S> Copy for As<T, S> {}
+
+impl<T: Default, S> Default for As<T, S> {
+    #[inline]
+    fn default() -> Self {
+        As::new(T::default())
+    }
+}
+
+impl<T: PartialEq, S> PartialEq for As<T, S> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, S> Eq for As<T, S> {}
+
+impl<T: PartialOrd, S> PartialOrd for As<T, S> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord, S> Ord for As<T, S> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: Debug, S> Debug for As<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// Encodes `value` using the strategy `S`, without wrapping it in [`As`].
+#[inline]
+pub fn encode_as<S: CodecWith<T>, T>(value: &T, io: &mut impl Write) -> Result<(), WriteError> {
+    S::encode(value, io)
+}
+
+/// Decodes a `T` using the strategy `S`, without wrapping it in [`As`].
+#[inline]
+pub fn decode_as<S: CodecWith<T>, T>(io: &mut impl Read) -> Result<T, ReadError> {
+    S::decode(io)
+}
+
+#[cfg(test)]
+use crate::impls::{encode_bytes, roundtrip};
+
+#[cfg(test)]
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+#[cfg(test)]
+use core::time::Duration;
+
+/// Encodes a `Duration` as whole seconds followed by subsecond nanos.
+#[cfg(test)]
+struct SecsNanos;
+
+#[cfg(test)]
+impl CodecWith<Duration> for SecsNanos {
+    fn encode(value: &Duration, io: &mut impl Write) -> Result<(), WriteError> {
+        value.as_secs().encode(io)?;
+        value.subsec_nanos().encode(io)
+    }
+
+    fn decode(io: &mut impl Read) -> Result<Duration, ReadError> {
+        let secs = u64::decode(io)?;
+        let nanos = u32::decode(io)?;
+        if nanos >= 1_000_000_000 {
+            return Err(ReadError::Overflow);
+        }
+        Ok(Duration::new(secs, nanos))
+    }
+}
+
+/// Encodes a `Duration` as whole milliseconds.
+#[cfg(test)]
+struct Millis;
+
+#[cfg(test)]
+impl CodecWith<Duration> for Millis {
+    fn encode(value: &Duration, io: &mut impl Write) -> Result<(), WriteError> {
+        (value.as_millis() as u64).encode(io)
+    }
+
+    fn decode(io: &mut impl Read) -> Result<Duration, ReadError> {
+        u64::decode(io).map(Duration::from_millis)
+    }
+}
+
+#[test]
+fn test_as() {
+    let value = As::<_, SecsNanos>::new(Duration::new(2, 5));
+    assert_eq!(roundtrip(value), [2, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0]);
+    let millis = As::<_, Millis>::new(Duration::from_millis(300));
+    assert_eq!(roundtrip(millis), [44, 1, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(*millis, Duration::from_millis(300));
+}
+
+#[test]
+fn test_as_composes() {
+    let durations: Vec<As<Duration, Millis>> = vec![
+        Duration::from_millis(1).into(),
+        Duration::from_millis(2).into(),
+    ];
+    assert_eq!(roundtrip(durations), encode_bytes(&vec![1u64, 2]));
+    roundtrip(Some(As::<_, SecsNanos>::new(Duration::MAX)));
+    roundtrip(None::<As<Duration, SecsNanos>>);
+    let mut map = BTreeMap::new();
+    map.insert(1u8, As::<_, Millis>::new(Duration::from_secs(1)));
+    roundtrip(map);
+}
+
+#[test]
+fn test_as_helpers() {
+    let mut buf = [0u8; 12];
+    let mut slice = crate::bytes::ByteSlice::from_slice_mut(&mut buf);
+    encode_as::<SecsNanos, _>(&Duration::new(1, 2), &mut slice).unwrap();
+    assert_eq!(buf, encode_bytes(&(1u64, 2u32))[..]);
+    let decoded: Duration = decode_as::<SecsNanos, _>(&mut &buf[..]).unwrap();
+    assert_eq!(decoded, Duration::new(1, 2));
+}
+
+#[test]
+fn test_as_strategy_error() {
+    let bytes = encode_bytes(&(0u64, u32::MAX));
+    assert!(matches!(
+        As::<Duration, SecsNanos>::decode(&mut &bytes[..]),
+        Err(ReadError::Overflow)
+    ));
+}